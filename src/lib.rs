@@ -9,431 +9,5175 @@
 //! use sentinel_list::*;
 //!
 //! fn main() {
-//!	 let l = &mut List::new();
-//!	 let h1 = l.push_head(1);
-//!	 let h2 = l.push_tail(2);
-//!	 let h3 = l.push_tail(3);
-//!	 {
-//!		assert_eq!(&1, (&h1).as_ref());
-//!		assert_eq!(&1, l.peek_head().unwrap());
-//!		assert_eq!(&3, l.peek_tail().unwrap());
-//!	 }
-//!	 let e1 = h1.unlink();
-//!	 assert_eq!(&2, l.peek_head().unwrap());
-//!	 let e3 = h3.unlink();
-//!	 assert_eq!(&2, l.peek_tail().unwrap());
-//!	 let e2 = h2.unlink();
-//!	 assert_eq!(None, l.peek_tail());
-//!	 assert_eq!(None, l.peek_head());
-//!	 assert_eq!(1, e1);
-//!	 assert_eq!(2, e2);
-//!	 assert_eq!(3, e3);
+//!     let l = &mut List::new();
+//!     let h1 = l.push_head(1);
+//!     let h2 = l.push_tail(2);
+//!     let h3 = l.push_tail(3);
+//!     {
+//!         assert_eq!(&1, (&h1).as_ref());
+//!         assert_eq!(&1, l.peek_head().unwrap());
+//!         assert_eq!(&3, l.peek_tail().unwrap());
+//!     }
+//!     let e1 = h1.unlink();
+//!     assert_eq!(&2, l.peek_head().unwrap());
+//!     let e3 = h3.unlink();
+//!     assert_eq!(&2, l.peek_tail().unwrap());
+//!     let e2 = h2.unlink();
+//!     assert_eq!(None, l.peek_tail());
+//!     assert_eq!(None, l.peek_head());
+//!     assert_eq!(1, e1);
+//!     assert_eq!(2, e2);
+//!     assert_eq!(3, e3);
 //! }
 //! ```
+//!
+//! The crate is `no_std` when built without the default `std` feature,
+//! it only needs `alloc` for boxing the links.
+//!
+//! With the `serde` feature a `List` serializes as a sequence of its elements.
+//!
+//! Pooling links is opt-in: `reserve(n)` puts n empty links in a pool the
+//! next pushes take from, and from then on up to n of the links the list
+//! removes go back to the pool instead of the allocator. Like a `Vec` that
+//! keeps its capacity the list holds on to that memory until
+//! `shrink_to_fit`. `clear` and dropping the list free links right away,
+//! links owned by a handle are freed by the handle and never pooled.
+//!
+//! # Ownership
+//!
+//! Every element has exactly one owner of its link: the handle returned by
+//! a push, or the list itself for elements added without one (`extend`,
+//! `collect`, cursors, ...). Whoever owns the link frees it.
+//! A list that removes an element whose handle is still alive (`pop_*`,
+//! `clear`, the `head_handle` views, dropping the list, ...) only moves
+//! the value out and leaves the empty link to the handle, whose later
+//! `try_unlink` gives the handle back instead of a value.
+//!
+//! A `head_handle` view or an `iter_handles` guard borrows the list but not
+//! the push handle, so the handle can still go away while the view is
+//! alive: dropping it leaves
+//! the element in the list as `detach` would, and once the handle unlinked
+//! the element everything the view does panics.
+//!
+//! Every link, the sentinel included, is on the heap, boxed on its own or,
+//! after `compact`, in one allocation with the list's other links. A `List`
+//! only points at them, so moving the list somewhere else doesn't move any
+//! link and the handles stay valid.
+//!
+//! Handles are owning: dropping one unlinks its element and drops the
+//! value, even if the handle was never used. To keep an element in the
+//! list without holding on to its handle, `detach` it, the list owns the
+//! element from then on.
+//!
+//! ```rust
+//! extern crate sentinel_list;
+//! use sentinel_list::*;
+//!
+//! fn main() {
+//!     let l = &mut List::new();
+//!     {
+//!         let _dropped = l.push_tail(1);
+//!         l.push_tail(2).detach();
+//!     }
+//!     assert_eq!(vec![&2], l.iter().collect::<Vec<_>>());
+//! }
+//! ```
+//!
+//! # Threads
+//!
+//! A handle doesn't borrow the list it came from, yet unlinking or dropping
+//! it rewires the neighbouring links and updates the list's length.
+//! Sending a list to another thread while one of its handles stays behind
+//! would let both threads mutate the same chain, so neither `List` nor
+//! the handles are `Send` or `Sync`.
+//!
+//! ```rust,compile_fail
+//! extern crate sentinel_list;
+//! use sentinel_list::*;
+//!
+//! fn assert_send<T: Send>() {}
+//!
+//! fn main() {
+//!     assert_send::<List<i32>>();
+//! }
+//! ```
+
+// the tests use std either way
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+
+#[cfg(any(feature = "std", test))]
+extern crate core;
+extern crate alloc;
+#[cfg(feature = "serde")]
+extern crate serde;
 
-#![feature(conservative_impl_trait)]
+use core::ptr::NonNull;
+use core::mem;
+use core::fmt;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::cell::{Cell, RefCell};
+use core::iter::{FromIterator, FusedIterator, Sum};
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut, Index, IndexMut};
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
 
-use std::ptr;
-use std::fmt;
-use std::ops::{Deref, DerefMut};
+// builds a List the way vec! builds a Vec, the list owns all the links
+#[macro_export]
+macro_rules! list {
+	() => (
+		$crate::List::new()
+	);
+	($elem:expr; $n:expr) => (
+		$crate::List::from_elem($elem, $n)
+	);
+	($($x:expr),+ $(,)*) => ({
+		let mut l = $crate::List::new();
+		$( l.extend(Some($x)); )+
+		l
+	});
+}
 
+mod sealed
+{
+	// keeps ListHandle implementable only by this crate
+	// and gives the list access to the link behind a handle
+	#[allow(private_interfaces)]
+	pub trait HasLink<T>
+	{
+		fn link(&self) -> *mut super::Link<T>;
+	}
+}
 
-pub trait ListHandle<T>
+pub trait ListHandle<T>: sealed::HasLink<T>
 {
 	fn unlink(self) -> T;
+	fn try_unlink(self) -> Result<T, Self> where Self: Sized;
 	fn as_ref(&self) -> &T;
+	fn try_as_ref(&self) -> Option<&T>;
+	fn as_mut(&mut self) -> &mut T;
+	fn move_to_head(&mut self);
+	fn move_to_tail(&mut self);
+	fn detach(self);
+	fn is_linked(&self) -> bool;
+	fn peek_next(&self) -> Option<&T>;
+	fn peek_prev(&self) -> Option<&T>;
+	fn index(&self) -> usize;
 }
 
 pub struct Iter<'a, T: 'a>
 {
 	next: &'a Link<T>,
+	back: &'a Link<T>,
+	len: usize,
 }
 
 pub struct IterMut<'a, T: 'a>
 {
-	next: Option<&'a mut Link<T>>,
+	next: *mut Link<T>,
+	back: *mut Link<T>,
+	len: usize,
+	marker: PhantomData<&'a mut Link<T>>,
+}
+
+pub struct IterRev<'a, T: 'a>
+{
+	inner: Iter<'a, T>,
+}
+
+pub struct IterRevMut<'a, T: 'a>
+{
+	inner: IterMut<'a, T>,
+}
+
+pub struct Chunks<'a, T: 'a>
+{
+	inner: Iter<'a, T>,
+	n: usize,
+}
+
+// runs of adjacent elements, the element that broke a run starts the next
+pub struct GroupRuns<'a, T: 'a, F>
+{
+	inner: Iter<'a, T>,
+	pending: Option<&'a T>,
+	same: F,
+}
+
+pub struct StrideIter<'a, T: 'a>
+{
+	inner: Iter<'a, T>,
+	step: usize,
+	first: bool,
+}
+
+// goes round and round, stepping over the sentinel each lap
+pub struct CycleIter<'a, T: 'a>
+{
+	list: &'a List<T>,
+	next: *mut Link<T>,
+}
+
+// keeps the last window around and slides it one element at a time
+pub struct Windows<'a, T: 'a>
+{
+	inner: Iter<'a, T>,
+	window: Vec<&'a T>,
+	n: usize,
+}
+
+pub struct IntoIter<T>
+{
+	list: List<T>,
+}
+
+// empties the list even when dropped half way
+pub struct Drain<'a, T: 'a>
+{
+	list: &'a mut List<T>,
+}
+
+pub struct Cursor<'a, T: 'a>
+{
+	current: *mut Link<T>,
+	list: &'a List<T>,
 }
 
+pub struct CursorMut<'a, T: 'a>
+{
+	current: *mut Link<T>,
+	list: &'a mut List<T>,
+}
+
+// a borrowed view of a linked element that can unlink it,
+// borrowing the list mutably so there is one of these at a time
+pub struct HandleRef<'a, T: 'a>
+{
+	list: &'a mut List<T>,
+	link: *mut Link<T>,
+}
+
+pub struct ExtractIf<'a, T: 'a, F>
+{
+	list: &'a mut List<T>,
+	next: *mut Link<T>,
+	pred: F,
+}
+
+// the head of a list if there is one, like HashMap's Entry
+pub enum HeadEntry<'a, T: 'a>
+{
+	Occupied(&'a mut T),
+	Vacant(&'a mut List<T>),
+}
+
+// hands out a guard for every element, the next link is read
+// before a guard is handed out so removing through it is fine
+pub struct IterHandles<'a, T: 'a>
+{
+	list: *mut List<T>,
+	next: *mut Link<T>,
+	end: *mut Link<T>,
+	marker: PhantomData<&'a mut List<T>>,
+}
+
+// several of these can be alive at once, each only reaches its own link;
+// like a HandleRef it keeps the link alive if the push handle goes away
+pub struct HandleGuard<'a, T: 'a>
+{
+	list: *mut List<T>,
+	link: *mut Link<T>,
+	marker: PhantomData<&'a mut List<T>>,
+}
+
+// deliberately !Send and !Sync, see the crate docs
 pub struct List<T>
 {
 	sentinel: Handle<T>,
+	shared: Rc<Shared<T>>,
+	// emptied links kept around for the next push
+	pool: Vec<Box<Link<T>>>,
+	// how many links reserve asked to keep pooled
+	pool_max: usize,
+	// every arena one of the links may live in
+	arenas: Vec<Rc<Arena<T>>>,
+}
+
+// state shared between a list and its links
+#[derive(PartialEq)]
+struct Shared<T>
+{
+	len: Cell<usize>,
+	sentinel: *mut Link<T>,
+	// set once the list's links were moved into another list,
+	// links still pointing here get redirected there
+	forward: RefCell<Option<Rc<Shared<T>>>>,
+}
+
+// links compact put next to each other, freed all at once when the last
+// list that may hold one of them goes away
+struct Arena<T>
+{
+	links: NonNull<[Link<T>]>,
 }
 
 #[derive(PartialEq)]
+// while linked next and prev always point at live links,
+// wrapping around through the sentinel; None in both is
+// how an unlinked link looks and the only time they're None
 struct Link<T>
 {
-	pub next: *mut Link<T>,
-	pub prev: *mut Link<T>,
+	pub next: Option<NonNull<Link<T>>>,
+	pub prev: Option<NonNull<Link<T>>>,
 	pub value: Option<T>,
+	// set while linked into a list so that a handle
+	// on its own can keep the list length right
+	// and find the list's sentinel
+	list: Option<Rc<Shared<T>>>,
+	// whether a Handle owns (and will free) this link,
+	// otherwise the list does
+	handle: bool,
+	// set while a HandleRef or a HandleGuard points here,
+	// a handle dropped meanwhile leaves the box alone
+	viewed: bool,
+	// part of an Arena rather than boxed on its own, only ever list owned
+	arena: bool,
 }
 
-struct Handle<T>(*mut Link<T>);
+// never dangles, the box behind it lives as long as the handle
+struct Handle<T>(NonNull<Link<T>>);
 
 impl<T> List<T>
 {
 	pub fn new() -> Self
 	{
-		List {
-			sentinel: Handle::new_sentinel(),
-		}
+		let sentinel: Handle<T> = Handle::new_sentinel();
+		let shared = Shared::new(sentinel.as_ptr());
+		List { sentinel, shared, pool: Vec::new(), pool_max: 0, arenas: Vec::new() }
+	}
+
+	#[doc(hidden)]
+	pub fn from_elem(e: T, n: usize) -> Self
+		where T: Clone
+	{
+		core::iter::repeat_n(e, n).collect()
+	}
+
+	pub fn from_slice(s: &[T]) -> Self
+		where T: Clone
+	{
+		s.iter().cloned().collect()
+	}
+
+	pub fn len(&self) -> usize
+	{
+		self.shared.len.get()
+	}
+
+	pub fn is_empty(&self) -> bool
+	{
+		self.len() == 0
 	}
 
 	pub fn push_head(&mut self, e: T) -> impl ListHandle<T>
 	{
-		let mut h = Handle::new(e);
-		insert_after(&mut self.sentinel, &mut h);
-		h
+		let s: *mut Link<T> = &mut *self.sentinel;
+		self.link_after(s, e)
 	}
 
 	pub fn push_tail(&mut self, e: T) -> impl ListHandle<T>
 	{
-		let mut h = Handle::new(e);
-		insert_after(unsafe {&mut *self.sentinel.prev}, &mut h);
-		h
+		self.push_tail_handle(e)
+	}
+
+	// std::collections::LinkedList names for the push family
+	pub fn push_front(&mut self, e: T) -> impl ListHandle<T>
+	{
+		self.push_head(e)
+	}
+
+	pub fn push_back(&mut self, e: T) -> impl ListHandle<T>
+	{
+		self.push_tail(e)
+	}
+
+	// also tells the index the element landed at
+	pub fn push_tail_at(&mut self, e: T) -> (impl ListHandle<T>, usize)
+	{
+		let index = self.len();
+		(self.push_tail_handle(e), index)
+	}
+
+	// goes in front of the first greater element, so after equal ones
+	pub fn insert_sorted(&mut self, e: T) -> impl ListHandle<T>
+		where T: Ord
+	{
+		let s = self.sentinel.as_ptr();
+		let mut link = self.sentinel.next();
+		while link != s && unsafe { &**link } <= &e {
+			link = unsafe { (*link).next() };
+		}
+		let prev = unsafe { (*link).prev() };
+		self.link_after(prev, e)
+	}
+
+	// gives the element back when the list already holds max of them
+	pub fn push_tail_bounded(&mut self, e: T, max: usize) -> Result<impl ListHandle<T>, T>
+	{
+		if self.len() >= max {
+			return Err(e);
+		}
+		Ok(self.push_tail_handle(e))
+	}
+
+	// a ring of at most cap elements, the list owns them
+	// and a push past cap gives back the head it evicted;
+	// a list already holding more than cap panics
+	pub fn push_tail_evicting(&mut self, e: T, cap: usize) -> Option<T>
+	{
+		assert!(self.len() <= cap,
+			"push_tail_evicting cap (is {}) should be >= len (is {})", cap, self.len());
+		if cap == 0 {
+			return Some(e);
+		}
+		self.push_tail_handle(e).release();
+		if self.len() > cap {
+			return self.pop_head();
+		}
+		None
+	}
+
+	fn push_tail_handle(&mut self, e: T) -> Handle<T>
+	{
+		let tail = self.sentinel.prev();
+		self.link_after(tail, e)
+	}
+
+	// the handle has to be linked into this list, this panics otherwise
+	pub fn insert_after<H: ListHandle<T>>(&mut self, handle: &H, e: T) -> impl ListHandle<T>
+	{
+		let link = self.own_link(handle);
+		self.link_after(link, e)
+	}
+
+	pub fn insert_before<H: ListHandle<T>>(&mut self, handle: &H, e: T) -> impl ListHandle<T>
+	{
+		let link = self.own_link(handle);
+		let prev = unsafe { (*link).prev() };
+		self.link_after(prev, e)
 	}
 
 	pub fn peek_head(&self) -> Option<&T>
 	{
-		let link = unsafe { &*self.sentinel.next };
+		let link = unsafe { &*self.sentinel.next() };
 		link.value.as_ref()
 	}
 	
 	pub fn peek_head_mut(&mut self) -> Option<&mut T>
 	{
-		let link = unsafe { &mut *self.sentinel.next };
+		let link = unsafe { &mut *self.sentinel.next() };
 		link.value.as_mut()
 	}
 
+	pub fn entry_head(&mut self) -> HeadEntry<'_, T>
+	{
+		if self.is_empty() {
+			return HeadEntry::Vacant(self);
+		}
+		HeadEntry::Occupied(self.peek_head_mut().unwrap())
+	}
+
 	pub fn peek_tail(&self) -> Option<&T>
 	{
-		let link = unsafe { &*self.sentinel.prev };
+		let link = unsafe { &*self.sentinel.prev() };
 		link.value.as_ref()
 	}
 
 	pub fn peek_tail_mut(&mut self) -> Option<&mut T>
 	{
-		let link = unsafe { &mut *self.sentinel.prev };
+		let link = unsafe { &mut *self.sentinel.prev() };
 		link.value.as_mut()
 	}
 
-	pub fn iter(&self) -> Iter<T>
+	// std::collections::LinkedList names for the peek family
+	pub fn front(&self) -> Option<&T>
 	{
-		Iter { next: unsafe {&*self.sentinel.next} }
+		self.peek_head()
 	}
 
-	pub fn iter_mut(&mut self) -> IterMut<T>
+	pub fn front_mut(&mut self) -> Option<&mut T>
 	{
-		let next = Some(unsafe {&mut *self.sentinel.next});
-		let inext = next.map(|v| {
-			v
-		});
-		IterMut { next: inext }
+		self.peek_head_mut()
 	}
-}
 
-impl<'a, T> Iterator for Iter<'a, T>
-{
-	type Item = &'a T;
+	pub fn back(&self) -> Option<&T>
+	{
+		self.peek_tail()
+	}
 
-	fn next(&mut self) -> Option<Self::Item>
+	pub fn back_mut(&mut self) -> Option<&mut T>
 	{
-		self.next.value.as_ref().and_then(|v| {
-			self.next = unsafe {&*self.next.next};
-			Some(v)
-		})
+		self.peek_tail_mut()
 	}
-}
 
-impl<'a, T> Iterator for IterMut<'a, T>
-{
-	type Item = &'a mut T;
+	// swaps the value in place, the link and its handle stay as they are
+	pub fn replace_head(&mut self, v: T) -> Option<T>
+	{
+		self.peek_head_mut().map(|e| mem::replace(e, v))
+	}
 
-	fn next(&mut self) -> Option<Self::Item>
+	pub fn replace_tail(&mut self, v: T) -> Option<T>
 	{
-		self.next.take().and_then(|link| {
-			self.next = Some(unsafe {&mut *link.next});
-			link.value.as_mut().map(|v| {
-				v
-			})
-		})
+		self.peek_tail_mut().map(|e| mem::replace(e, v))
 	}
-}
 
-impl<T> Link<T>
-{
-	fn new(v: T) -> Link<T>
+	// a way to unlink or move the current head without having kept its
+	// handle, None on an empty list; the view borrows the list mutably so
+	// nothing else changes it while the view is held, a push handle still
+	// alive for the element finds it unlinked afterwards
+	pub fn head_handle(&mut self) -> Option<HandleRef<'_, T>>
 	{
-		Link {
-			prev: ptr::null_mut(),
-			next: ptr::null_mut(),
-			value: Some(v),
+		if self.is_empty() {
+			return None;
 		}
+		let link = self.sentinel.next();
+		Some(HandleRef::new(self, link))
 	}
 
-    // just unlinks
-    // after calling this it's unsafe to use
-    // this Handle without adjusting prev, next
-    fn unlink(&mut self)
-    {
-        let prev = unsafe { &mut *self.prev };
-        let next = unsafe { &mut *self.next };
-        next.prev = prev;
-        prev.next = next;
-    }
+	pub fn tail_handle(&mut self) -> Option<HandleRef<'_, T>>
+	{
+		if self.is_empty() {
+			return None;
+		}
+		let link = self.sentinel.prev();
+		Some(HandleRef::new(self, link))
+	}
 
-}
-impl<T> Handle<T>
-{
-    fn new(v: T) -> Self
-    {
-	    Handle(Box::into_raw(Box::new(Link::new(v))))
-    }
+	pub fn pop_head(&mut self) -> Option<T>
+	{
+		if self.is_empty() {
+			return None;
+		}
+		let link = self.sentinel.next();
+		self.remove_link(link)
+	}
 
-	fn new_sentinel() -> Self 
+	pub fn pop_tail(&mut self) -> Option<T>
 	{
-		let mut h = Box::new(
-			Link { 
-				prev: ptr::null_mut(), 
-				next: ptr::null_mut(),
-				value: None,
-			}
-		);
-		h.prev = &mut *h;
-		h.next = &mut *h;
-		Handle(Box::into_raw(h))
+		if self.is_empty() {
+			return None;
+		}
+		let link = self.sentinel.prev();
+		self.remove_link(link)
 	}
-    
-    fn into_inner(self) -> Option<T>
-    {
-        let mut h = self;
-        let link = unsafe { &mut *h.0 };
-        link.unlink();
-        link.value.take()
-    }
-}
 
-impl<T: fmt::Debug> fmt::Debug for Link<T>
-{
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "Link {{ prev: {:p}, next: {:p}, value: {:?} }}", self.prev, self.next, self.value)
+	// the bool tells whether that emptied the list
+	pub fn take_head(&mut self) -> Option<(T, bool)>
+	{
+		self.pop_head().map(|v| (v, self.is_empty()))
 	}
-}
 
-impl<T> Drop for Handle<T>
-{
-    fn drop(&mut self)
-    {
-        let link = unsafe { &mut *self.0 };
-        link.unlink();
-        if !self.0.is_null() {
-            let h = unsafe { Box::from_raw(self.0) };
-            drop(h);
-        }
-        // not sure if this matters
-        //println!("Drop");
-    }
-}
+	pub fn take_tail(&mut self) -> Option<(T, bool)>
+	{
+		self.pop_tail().map(|v| (v, self.is_empty()))
+	}
 
-impl<T> ListHandle<T> for Handle<T>
-{
-	fn unlink(self) -> T
+	// frees the links, none go to the pool
+	pub fn clear(&mut self)
 	{
-		// if you call it on the sentinel 
-		// you deserve to get what's coming
-		self.into_inner().unwrap()
+		let s: *mut Link<T> = &mut *self.sentinel;
+		let mut link = self.sentinel.next();
+		while link != s {
+			let next = unsafe { (*link).next() };
+			self.take_link(link, false);
+			link = next;
+		}
 	}
 
-	fn as_ref(&self) -> &T
+	// drops everything past the first len elements, from the tail end
+	pub fn truncate(&mut self, len: usize)
 	{
-		&self
+		while self.len() > len {
+			self.pop_tail();
+		}
 	}
-}
 
-impl<T> Deref for Link<T>
-{
-	type Target = T;
-	fn deref(&self) -> &Self::Target
+	pub fn append(&mut self, other: &mut List<T>)
 	{
-		// if you call it on the sentinel 
-		// you deserve to get what's coming
-		self.value.as_ref().unwrap()
+		let tail = self.sentinel.prev();
+		self.splice(tail, other);
 	}
-}
 
-impl<T> Deref for Handle<T>
-{
-	type Target = Link<T>;
-	fn deref(&self) -> &Self::Target
+	pub fn prepend(&mut self, other: &mut List<T>)
 	{
-		unsafe {&*self.0}
+		let s = self.sentinel.as_ptr();
+		self.splice(s, other);
 	}
-}
 
-impl<T> DerefMut for Handle<T>
-{
-	fn deref_mut(&mut self) -> &mut Self::Target
+	// moves all of other in right after the handle, other ends up empty
+	pub fn splice_after<H: ListHandle<T>>(&mut self, handle: &H, other: &mut List<T>)
 	{
-		unsafe {&mut *self.0}
+		let link = self.own_link(handle);
+		self.splice(link, other);
 	}
-}
 
-fn insert_after<T>(after: &mut Link<T>, h: &mut Link<T>)
-{
-	let n: *mut _ = after.next;
+	pub fn concat(lists: Vec<List<T>>) -> List<T>
+	{
+		let mut l = List::new();
+		for mut other in lists {
+			l.append(&mut other);
+		}
+		l
+	}
 
-	h.prev = &mut *after;
-	h.next = n;
-	
-	after.next = &mut *h;
-	
-	let n = unsafe { &mut *h.next };
-	n.prev = &mut *h;
-}
+	// this list keeps everything up to the handle
+	pub fn split_after<H: ListHandle<T>>(&mut self, handle: &H) -> List<T>
+	{
+		let link = self.own_link(handle);
+		self.split_links(link)
+	}
 
-#[allow(dead_code)]
-#[cfg(test)]
-fn debug_print<T: fmt::Debug>(s: &mut Handle<T>)
-{
-	let mut h = s.next;
-	while h != &mut **s {
-		let n = unsafe { &*h };
-		println!("{:?}", n);
-		h = n.next;
+	// everything before the handle and everything from the handle on
+	pub fn split_at_handle<H: ListHandle<T>>(mut self, handle: &H) -> (List<T>, List<T>)
+	{
+		let link = self.own_link(handle);
+		let prev = unsafe { (*link).prev() };
+		let rest = self.split_links(prev);
+		(self, rest)
 	}
-}
+
+	// this list keeps [..index], the returned one gets [index..]
+	pub fn split_off(&mut self, index: usize) -> List<T>
+	{
+		assert!(index <= self.len(),
+			"split_off index (is {}) should be <= len (is {})", index, self.len());
+		let after = if index == 0 {
+			&mut *self.sentinel as *mut Link<T>
+		} else {
+			self.link_at(index - 1)
+		};
+		self.split_links(after)
+	}
+
+	// one pass from head to tail, each rejected element is dropped before
+	// f sees the next one; f gets the value in place so ptr::eq against
+	// a handle's as_ref tells which element it is looking at
+	pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F)
+	{
+		self.retain_mut(|v| f(v));
+	}
+
+	pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F)
+	{
+		let s: *mut Link<T> = &mut *self.sentinel;
+		let mut link = self.sentinel.next();
+		while link != s {
+			let next = unsafe { (*link).next() };
+			if !f(unsafe { (*link).value.as_mut().unwrap() }) {
+				self.remove_link(link);
+			}
+			link = next;
+		}
+	}
+
+	pub fn reverse(&mut self)
+	{
+		let s: *mut Link<T> = &mut *self.sentinel;
+		let mut link = s;
+		loop {
+			let l = unsafe { &mut *link };
+			mem::swap(&mut l.next, &mut l.prev);
+			// the old next is now prev
+			link = l.prev();
+			if link == s {
+				break;
+			}
+		}
+	}
+
+	// yields every element by value, the list is empty
+	// afterwards even if the Drain is dropped early
+	pub fn drain(&mut self) -> Drain<'_, T>
+	{
+		Drain { list: self }
+	}
+
+	// removes and yields the elements matching f as it's iterated,
+	// dropping it early leaves the rest of the list untouched
+	pub fn extract_if<F: FnMut(&mut T) -> bool>(&mut self, f: F) -> ExtractIf<'_, T, F>
+	{
+		let next = self.sentinel.next();
+		ExtractIf { list: self, next, pred: f }
+	}
+
+	pub fn iter_handles(&mut self) -> IterHandles<'_, T>
+	{
+		let end: *mut Link<T> = &mut *self.sentinel;
+		let next = self.sentinel.next();
+		IterHandles { list: self, next, end, marker: PhantomData }
+	}
+
+	// exchanges just the values, the links stay where they are;
+	// it takes the handles mutably as no one may be looking at either value
+	pub fn swap_values<A: ListHandle<T>, B: ListHandle<T>>(a: &mut A, b: &mut B)
+	{
+		mem::swap(a.as_mut(), b.as_mut());
+	}
+
+	// exchanges the positions of two links, the handles stay with their values
+	pub fn swap<A: ListHandle<T>, B: ListHandle<T>>(&mut self, a: &A, b: &B)
+	{
+		let pa = self.own_link(a);
+		let pb = self.own_link(b);
+		List::swap_links(pa, pb);
+	}
+
+	pub fn swap_ends(&mut self)
+	{
+		if self.len() < 2 {
+			return;
+		}
+		List::swap_links(self.sentinel.next(), self.sentinel.prev());
+	}
+
+	// the head moves n places on, so the first n elements end up at the tail
+	pub fn rotate_left(&mut self, n: usize)
+	{
+		if self.is_empty() {
+			return;
+		}
+		let n = n % self.len();
+		if n == 0 {
+			return;
+		}
+		let last = self.link_at(n - 1);
+		self.move_sentinel_after(last);
+	}
+
+	pub fn rotate_right(&mut self, n: usize)
+	{
+		if self.is_empty() {
+			return;
+		}
+		let len = self.len();
+		self.rotate_left(len - n % len);
+	}
+
+	// makes the handle's element the head, in O(1)
+	pub fn rotate_to_handle<H: ListHandle<T>>(&mut self, handle: &H)
+	{
+		let link = self.own_link(handle);
+		let prev = unsafe { (*link).prev() };
+		let s: *mut Link<T> = &mut *self.sentinel;
+		if prev != s {
+			self.move_sentinel_after(prev);
+		}
+	}
+
+	pub fn remove_first(&mut self, x: &T) -> Option<T>
+		where T: PartialEq
+	{
+		let s: *mut Link<T> = &mut *self.sentinel;
+		let mut link = self.sentinel.next();
+		while link != s {
+			if unsafe { &**link } == x {
+				return self.remove_link(link);
+			}
+			link = unsafe { (*link).next() };
+		}
+		None
+	}
+
+	pub fn dedup(&mut self)
+		where T: PartialEq
+	{
+		self.dedup_by(|a, b| a == b)
+	}
+
+	// like Vec::dedup_by, same gets the element and the one kept before it
+	pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, mut same: F)
+	{
+		if self.is_empty() {
+			return;
+		}
+		let s: *mut Link<T> = &mut *self.sentinel;
+		let mut kept = self.sentinel.next();
+		let mut link = unsafe { (*kept).next() };
+		while link != s {
+			let next = unsafe { (*link).next() };
+			let dup = unsafe {
+				same((*link).value.as_mut().unwrap(), (*kept).value.as_mut().unwrap())
+			};
+			if dup {
+				self.remove_link(link);
+			} else {
+				kept = link;
+			}
+			link = next;
+		}
+	}
+
+	pub fn cursor_head(&self) -> Cursor<'_, T>
+	{
+		Cursor { current: self.sentinel.next(), list: self }
+	}
+
+	pub fn cursor_tail(&self) -> Cursor<'_, T>
+	{
+		Cursor { current: self.sentinel.prev(), list: self }
+	}
+
+	pub fn cursor_head_mut(&mut self) -> CursorMut<'_, T>
+	{
+		CursorMut { current: self.sentinel.next(), list: self }
+	}
+
+	pub fn cursor_tail_mut(&mut self) -> CursorMut<'_, T>
+	{
+		CursorMut { current: self.sentinel.prev(), list: self }
+	}
+
+	pub fn iter(&self) -> Iter<'_, T>
+	{
+		Iter {
+			next: unsafe {&*self.sentinel.next()},
+			back: unsafe {&*self.sentinel.prev()},
+			len: self.len(),
+		}
+	}
+
+	pub fn iter_mut(&mut self) -> IterMut<'_, T>
+	{
+		IterMut {
+			next: self.sentinel.next(),
+			back: self.sentinel.prev(),
+			len: self.len(),
+			marker: PhantomData,
+		}
+	}
+
+	pub fn iter_rev(&self) -> IterRev<'_, T>
+	{
+		IterRev { inner: self.iter() }
+	}
+
+	pub fn iter_rev_mut(&mut self) -> IterRevMut<'_, T>
+	{
+		IterRevMut { inner: self.iter_mut() }
+	}
+
+	// the last chunk may be shorter, n can't be 0
+	pub fn chunks(&self, n: usize) -> Chunks<'_, T>
+	{
+		assert!(n != 0, "chunk size must be non-zero");
+		Chunks { inner: self.iter(), n }
+	}
+
+	// overlapping windows of n elements, none if the list is shorter
+	pub fn windows(&self, n: usize) -> Windows<'_, T>
+	{
+		assert!(n != 0, "window size must be non-zero");
+		Windows { inner: self.iter(), window: Vec::with_capacity(n), n }
+	}
+
+	// the elements at 0, step, 2 * step, ...
+	pub fn stride_iter(&self, step: usize) -> StrideIter<'_, T>
+	{
+		assert!(step != 0, "step must be non-zero");
+		StrideIter { inner: self.iter(), step, first: true }
+	}
+
+	// never ends unless the list is empty
+	pub fn cycle_iter(&self) -> CycleIter<'_, T>
+	{
+		CycleIter { list: self, next: self.sentinel.next() }
+	}
+
+	// same is asked about each element and the one before it
+	pub fn group_runs<F: FnMut(&T, &T) -> bool>(&self, same: F) -> GroupRuns<'_, T, F>
+	{
+		GroupRuns { inner: self.iter(), pending: None, same }
+	}
+
+	pub fn contains(&self, x: &T) -> bool
+		where T: PartialEq
+	{
+		self.iter().any(|v| v == x)
+	}
+
+	pub fn find<F: Fn(&T) -> bool>(&self, f: F) -> Option<&T>
+	{
+		self.iter().find(|v| f(v))
+	}
+
+	pub fn find_mut<F: Fn(&T) -> bool>(&mut self, f: F) -> Option<&mut T>
+	{
+		self.iter_mut().find(|v| f(v))
+	}
+
+	pub fn get(&self, index: usize) -> Option<&T>
+	{
+		self.iter().nth(index)
+	}
+
+	pub fn get_mut(&mut self, index: usize) -> Option<&mut T>
+	{
+		self.iter_mut().nth(index)
+	}
+
+	// the link and any handle for it stay, only the value changes
+	pub fn replace_at(&mut self, index: usize, v: T) -> Option<T>
+	{
+		self.get_mut(index).map(|e| mem::replace(e, v))
+	}
+
+	// counts from the tail, 0 is the last element
+	pub fn get_back(&self, index: usize) -> Option<&T>
+	{
+		self.iter().rev().nth(index)
+	}
+
+	pub fn sum(&self) -> T
+		where T: Copy + Sum
+	{
+		self.iter().copied().sum()
+	}
+
+	pub fn count_if<F: Fn(&T) -> bool>(&self, f: F) -> usize
+	{
+		self.iter().filter(|v| f(v)).count()
+	}
+
+	pub fn position<F: Fn(&T) -> bool>(&self, f: F) -> Option<usize>
+	{
+		self.iter().position(f)
+	}
+
+	// scans from the tail, the index still counts from the head
+	pub fn rposition<F: FnMut(&T) -> bool>(&self, f: F) -> Option<usize>
+	{
+		self.iter().rposition(f)
+	}
+
+	pub fn for_each<F: FnMut(&mut T)>(&mut self, f: F)
+	{
+		self.iter_mut().for_each(f);
+	}
+
+	pub fn map_in_place<F: FnMut(&T) -> T>(&mut self, mut f: F)
+	{
+		for e in self.iter_mut() {
+			*e = f(e);
+		}
+	}
+
+	pub fn to_vec(&self) -> Vec<T>
+		where T: Clone
+	{
+		let mut v = Vec::with_capacity(self.len());
+		v.extend(self.iter().cloned());
+		v
+	}
+
+	// both lists have to be sorted already, other ends up empty,
+	// equal elements keep the ones from self in front
+	pub fn merge_sorted(&mut self, other: &mut List<T>)
+		where T: Ord
+	{
+		let s: *mut Link<T> = &mut *self.sentinel;
+		let b = other.sentinel.next();
+		if other.is_empty() {
+			return;
+		}
+		self.append(other);
+		List::merge_links(self.sentinel.next(), b, s, &mut |x: &T, y: &T| x.cmp(y));
+	}
+
+	pub fn sort(&mut self)
+		where T: Ord
+	{
+		self.sort_by(|a, b| a.cmp(b));
+	}
+
+	// bottom up merge sort that only relinks, handles stay with their values
+	pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut cmp: F)
+	{
+		let s: *mut Link<T> = &mut *self.sentinel;
+		let len = self.len();
+		let skip = |mut link: *mut Link<T>, n: usize| {
+			for _ in 0..n {
+				if link == s {
+					break;
+				}
+				link = unsafe { (*link).next() };
+			}
+			link
+		};
+		let mut width = 1;
+		while width < len {
+			let mut a = self.sentinel.next();
+			while a != s {
+				let b = skip(a, width);
+				let end = skip(b, width);
+				List::merge_links(a, b, end, &mut cmp);
+				a = end;
+			}
+			width *= 2;
+		}
+	}
+
+	// links the list holds, both in use and pooled
+	pub fn capacity(&self) -> usize
+	{
+		self.len() + self.pool.len()
+	}
+
+	// makes sure the next n pushes don't allocate
+	// and keeps up to n removed links pooled from now on
+	pub fn reserve(&mut self, n: usize)
+	{
+		self.pool_max = self.pool_max.max(n);
+		while self.pool.len() < n {
+			self.pool.push(Box::new(Link::empty()));
+		}
+	}
+
+	pub fn shrink_to_fit(&mut self)
+	{
+		self.pool = Vec::new();
+		self.pool_max = 0;
+	}
+
+	// same result as slice::binary_search_by but it's a linear scan, O(n)
+	pub fn search<F: FnMut(&T) -> Ordering>(&self, mut f: F) -> Result<usize, usize>
+	{
+		for (i, v) in self.iter().enumerate() {
+			match f(v) {
+				Ordering::Less => {}
+				Ordering::Equal => return Ok(i),
+				Ordering::Greater => return Err(i),
+			}
+		}
+		Err(self.len())
+	}
+
+	// relinks the elements f rejects into the second list, keeping the order
+	pub fn partition<F: FnMut(&T) -> bool>(mut self, mut f: F) -> (List<T>, List<T>)
+	{
+		let mut rejected: List<T> = List::new();
+		rejected.arenas = self.arenas.clone();
+		let s: *mut Link<T> = &mut *self.sentinel;
+		let mut link = self.sentinel.next();
+		while link != s {
+			let l = unsafe { &mut *link };
+			link = l.next();
+			if !f(l) {
+				l.splice_out();
+				self.shared.len.set(self.len() - 1);
+				insert_after(unsafe { &mut *rejected.sentinel.prev() }, l);
+				rejected.track(l);
+			}
+		}
+		(self, rejected)
+	}
+
+	// stops at the end of the shorter list
+	pub fn zip_map<U, R, F: FnMut(&T, &U) -> R>(&self, other: &List<U>, mut f: F) -> List<R>
+	{
+		self.iter().zip(other.iter()).map(|(a, b)| f(a, b)).collect()
+	}
+
+	// bytes taken by the links: the boxed elements, the pooled ones, the
+	// sentinel and every arena slot, used or not; O(n) after a compact as
+	// it walks the list to tell the boxed links from the arena ones
+	pub fn heap_bytes(&self) -> usize
+	{
+		let mut links = self.pool.len() + 1;
+		if self.arenas.is_empty() {
+			links += self.len();
+		} else {
+			links += self.arenas.iter().map(|a| a.links.len()).sum::<usize>();
+			let s = self.sentinel.as_ptr();
+			let mut link = self.sentinel.next();
+			while link != s {
+				let l = unsafe { &*link };
+				if !l.arena {
+					links += 1;
+				}
+				link = l.next();
+			}
+		}
+		links * mem::size_of::<Link<T>>()
+	}
+
+	// a method on the list rather than on the handle so the neighbours
+	// borrow the list, they are None at either end
+	pub fn unlink_with_neighbors<H: ListHandle<T>>(&mut self, handle: H) -> (T, Option<&T>, Option<&T>)
+	{
+		let link = self.own_link(&handle);
+		let (prev, next) = unsafe { ((*link).prev(), (*link).next()) };
+		let v = handle.unlink();
+		let (prev, next) = unsafe { (&*prev, &*next) };
+		(v, prev.value.as_ref(), next.value.as_ref())
+	}
+
+	pub fn into_vec(self) -> Vec<T>
+	{
+		let mut v = Vec::with_capacity(self.len());
+		v.extend(self);
+		v
+	}
+
+	// walks the chain both ways checking the links agree with each other
+	// and with len, meant for debug_assert!(l.validate()) and fuzzing
+	pub fn validate(&self) -> bool
+	{
+		let s = self.sentinel.as_ptr();
+		let len = self.len();
+		if self.sentinel.value.is_some() {
+			return false;
+		}
+		let walk = |forward: bool| {
+			let mut link = s;
+			for n in 0..len + 1 {
+				let l = unsafe { &*link };
+				if l.next.is_none() || l.prev.is_none() {
+					return false;
+				}
+				if unsafe { (*l.next()).prev() != link || (*l.prev()).next() != link } {
+					return false;
+				}
+				link = if forward { l.next() } else { l.prev() };
+				// only the sentinel goes without a value
+				let at_sentinel = link == s;
+				if at_sentinel != (n == len) || (!at_sentinel && unsafe { (*link).value.is_none() }) {
+					return false;
+				}
+			}
+			true
+		};
+		walk(true) && walk(false)
+	}
+
+	pub fn fold<B, F: FnMut(B, &T) -> B>(&self, init: B, f: F) -> B
+	{
+		self.iter().fold(init, f)
+	}
+
+	// f only ever sees references so the head gets cloned to start off
+	pub fn reduce<F: FnMut(&T, &T) -> T>(&self, mut f: F) -> Option<T>
+		where T: Clone
+	{
+		let mut i = self.iter();
+		let first = i.next()?.clone();
+		Some(i.fold(first, |acc, v| f(&acc, v)))
+	}
+
+	pub fn min(&self) -> Option<&T>
+		where T: Ord
+	{
+		self.iter().min()
+	}
+
+	pub fn max(&self) -> Option<&T>
+		where T: Ord
+	{
+		self.iter().max()
+	}
+
+	// moves the elements the list owns into one allocation, in list order
+	// so iteration walks memory forward. Links owned by a handle can't move
+	// and stay where they are, no handle is invalidated. Arena links removed
+	// later aren't reused, that memory comes back with the next compact or
+	// once no list holds a link of the arena anymore.
+	pub fn compact(&mut self)
+	{
+		let s = self.sentinel.as_ptr();
+		let mut owned = 0;
+		let mut link = self.sentinel.next();
+		while link != s {
+			let l = unsafe { &*link };
+			if !l.handle {
+				owned += 1;
+			}
+			link = l.next();
+		}
+		let arena = Arena::new(owned);
+
+		let mut n = 0;
+		let mut link = self.sentinel.next();
+		while link != s {
+			let old = unsafe { &mut *link };
+			link = old.next();
+			if old.handle {
+				continue;
+			}
+			let new = unsafe { &mut *arena.link(n) };
+			n += 1;
+			new.value = old.value.take();
+			new.list = old.list.take();
+			let prev = old.prev();
+			old.splice_out();
+			insert_after(unsafe { &mut *prev }, new);
+			if !old.arena {
+				drop(unsafe { Box::from_raw(old) });
+			}
+		}
+		// none of our links are in the old arenas now
+		self.arenas = Vec::new();
+		if owned != 0 {
+			self.arenas.push(Rc::new(arena));
+		}
+	}
+
+	// moves other's arenas over along with its links
+	fn take_arenas(&mut self, other: &mut List<T>)
+	{
+		for a in other.arenas.drain(..) {
+			if !self.arenas.iter().any(|b| Rc::ptr_eq(&a, b)) {
+				self.arenas.push(a);
+			}
+		}
+	}
+
+	fn link_after(&mut self, after: *mut Link<T>, e: T) -> Handle<T>
+	{
+		let mut h = match self.pool.pop() {
+			Some(mut link) => {
+				link.value = Some(e);
+				link.handle = true;
+				Handle::from_box(link)
+			}
+			None => Handle::new(e),
+		};
+		insert_after(unsafe {&mut *after}, &mut h);
+		self.track(&mut h);
+		h
+	}
+
+	// the link behind a handle, making sure it's linked into this list
+	fn own_link<H: ListHandle<T>>(&self, handle: &H) -> *mut Link<T>
+	{
+		let link = handle.link();
+		let ours = unsafe { (*link).shared() }
+			.is_some_and(|list| Rc::ptr_eq(&list, &self.shared));
+		assert!(ours, "handle is not linked into this list");
+		link
+	}
+
+	// moves all of other's links in between after and its next
+	// leaving other empty, the links keep pointing at other's
+	// old shared state which now forwards to ours
+	fn splice(&mut self, after: *mut Link<T>, other: &mut List<T>)
+	{
+		if other.is_empty() {
+			return;
+		}
+		let os: *mut Link<T> = &mut *other.sentinel;
+		unsafe {
+			let head = (*os).next();
+			let tail = (*os).prev();
+			let next = (*after).next();
+			(*after).next = NonNull::new(head);
+			(*head).prev = NonNull::new(after);
+			(*tail).next = NonNull::new(next);
+			(*next).prev = NonNull::new(tail);
+			(*os).next = NonNull::new(os);
+			(*os).prev = NonNull::new(os);
+		}
+		self.shared.len.set(self.len() + other.len());
+		*other.shared.forward.borrow_mut() = Some(self.shared.clone());
+		other.shared = Shared::new(os);
+		self.take_arenas(other);
+	}
+
+	// the link at index, walking from whichever end is closer
+	fn link_at(&self, index: usize) -> *mut Link<T>
+	{
+		assert!(index < self.len());
+		if index < self.len() / 2 {
+			let mut link = self.sentinel.next();
+			for _ in 0..index {
+				link = unsafe { (*link).next() };
+			}
+			link
+		} else {
+			let mut link = self.sentinel.prev();
+			for _ in index + 1..self.len() {
+				link = unsafe { (*link).prev() };
+			}
+			link
+		}
+	}
+
+	// re-splices the sentinel so that iteration starts after link
+	fn move_sentinel_after(&mut self, link: *mut Link<T>)
+	{
+		self.sentinel.splice_out();
+		insert_after(unsafe { &mut *link }, &mut self.sentinel);
+	}
+
+	// exchanges the positions of two links of the same list
+	fn swap_links(pa: *mut Link<T>, pb: *mut Link<T>)
+	{
+		if pa == pb {
+			return;
+		}
+		let a = unsafe { &mut *pa };
+		let b = unsafe { &mut *pb };
+		if a.next() == pb {
+			b.splice_out();
+			insert_after(unsafe { &mut *a.prev() }, b);
+		} else if b.next() == pa {
+			a.splice_out();
+			insert_after(unsafe { &mut *b.prev() }, a);
+		} else {
+			let ap = a.prev();
+			let bp = b.prev();
+			a.splice_out();
+			b.splice_out();
+			insert_after(unsafe { &mut *bp }, a);
+			insert_after(unsafe { &mut *ap }, b);
+		}
+	}
+
+	// merges the sorted runs [a..b) and [b..end) by relinking,
+	// on ties the element of the first run stays in front
+	fn merge_links<F>(mut a: *mut Link<T>, mut b: *mut Link<T>, end: *mut Link<T>, cmp: &mut F)
+		where F: FnMut(&T, &T) -> Ordering
+	{
+		while a != b && b != end {
+			let (x, y) = unsafe { (&mut *a, &mut *b) };
+			if cmp(y, x) == Ordering::Less {
+				let next = y.next();
+				y.splice_out();
+				insert_after(unsafe { &mut *x.prev() }, y);
+				b = next;
+			} else {
+				a = x.next();
+			}
+		}
+	}
+
+	// moves the links following after into a new list,
+	// they have to learn about their new list so it walks them
+	fn split_links(&mut self, after: *mut Link<T>) -> List<T>
+	{
+		let mut other = List::new();
+		let s: *mut Link<T> = &mut *self.sentinel;
+		let head = unsafe { (*after).next() };
+		if head == s {
+			return other;
+		}
+		let os: *mut Link<T> = &mut *other.sentinel;
+		let mut n = 0;
+		unsafe {
+			let tail = (*s).prev();
+			(*after).next = NonNull::new(s);
+			(*s).prev = NonNull::new(after);
+			(*os).next = NonNull::new(head);
+			(*head).prev = NonNull::new(os);
+			(*tail).next = NonNull::new(os);
+			(*os).prev = NonNull::new(tail);
+
+			let mut link = head;
+			while link != os {
+				(*link).list = Some(other.shared.clone());
+				n += 1;
+				link = (*link).next();
+			}
+		}
+		self.shared.len.set(self.len() - n);
+		other.shared.len.set(n);
+		// the moved part may hold links of any of our arenas
+		other.arenas = self.arenas.clone();
+		other
+	}
+
+	// counts a freshly inserted link against this list
+	fn track(&self, link: &mut Link<T>)
+	{
+		self.shared.len.set(self.shared.len.get() + 1);
+		link.list = Some(self.shared.clone());
+	}
+
+	// unlinks a real link and moves its value out,
+	// if a handle owns the box it is reclaimed by the handle
+	// which finds the link already unlinked and just frees it,
+	// otherwise the empty box is pooled while the pool is short
+	// of what reserve asked for and freed once it isn't
+	fn remove_link(&mut self, link: *mut Link<T>) -> Option<T>
+	{
+		let pool = self.pool.len() < self.pool_max;
+		self.take_link(link, pool)
+	}
+
+	fn take_link(&mut self, link: *mut Link<T>, pool: bool) -> Option<T>
+	{
+		let l = unsafe { &mut *link };
+		l.unlink();
+		let v = l.value.take();
+		if !l.handle && !l.arena {
+			let link = unsafe { Box::from_raw(link) };
+			if pool {
+				self.pool.push(link);
+			}
+		}
+		v
+	}
+}
+
+impl<'a, T> Iterator for Iter<'a, T>
+{
+	type Item = &'a T;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		// nothing left between front and back
+		if self.len == 0 {
+			return None;
+		}
+		self.len -= 1;
+		self.next.value.as_ref().inspect(|_| {
+			self.next = unsafe {&*self.next.next()};
+		})
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>)
+	{
+		(self.len, Some(self.len))
+	}
+
+	fn nth(&mut self, n: usize) -> Option<Self::Item>
+	{
+		if n >= self.len {
+			self.len = 0;
+			return None;
+		}
+		for _ in 0..n {
+			self.next = unsafe {&*self.next.next()};
+		}
+		self.len -= n;
+		self.next()
+	}
+
+	// the back end is right there, no need to walk
+	fn last(self) -> Option<Self::Item>
+	{
+		if self.len == 0 {
+			return None;
+		}
+		self.back.value.as_ref()
+	}
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T>
+{
+	fn len(&self) -> usize
+	{
+		self.len
+	}
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T>
+{
+	fn next_back(&mut self) -> Option<Self::Item>
+	{
+		if self.len == 0 {
+			return None;
+		}
+		self.len -= 1;
+		self.back.value.as_ref().inspect(|_| {
+			self.back = unsafe {&*self.back.prev()};
+		})
+	}
+}
+
+impl<'a, T> Iterator for IterMut<'a, T>
+{
+	type Item = &'a mut T;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		if self.len == 0 {
+			return None;
+		}
+		self.len -= 1;
+		let link = unsafe { &mut *self.next };
+		let next = link.next();
+		link.value.as_mut().inspect(|_| {
+			self.next = next;
+		})
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>)
+	{
+		(self.len, Some(self.len))
+	}
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T>
+{
+	fn len(&self) -> usize
+	{
+		self.len
+	}
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T>
+{
+	fn next_back(&mut self) -> Option<Self::Item>
+	{
+		if self.len == 0 {
+			return None;
+		}
+		self.len -= 1;
+		let link = unsafe { &mut *self.back };
+		let prev = link.prev();
+		link.value.as_mut().inspect(|_| {
+			self.back = prev;
+		})
+	}
+}
+
+// the push handle for the element may unlink it while the view is alive,
+// everything the view does after that panics
+impl<'a, T> HandleRef<'a, T>
+{
+	fn new(list: &'a mut List<T>, link: *mut Link<T>) -> Self
+	{
+		unsafe { (*link).viewed = true };
+		HandleRef { list, link }
+	}
+
+	pub fn get(&self) -> &T
+	{
+		unsafe { (*self.link).value.as_ref() }.expect("the element was unlinked through its handle")
+	}
+
+	pub fn get_mut(&mut self) -> &mut T
+	{
+		unsafe { (*self.link).value.as_mut() }.expect("the element was unlinked through its handle")
+	}
+
+	pub fn unlink(self) -> T
+	{
+		let mut r = mem::ManuallyDrop::new(self);
+		unsafe { (*r.link).viewed = false };
+		let link = r.link;
+		r.list.remove_link(link).expect("the element was unlinked through its handle")
+	}
+
+	pub fn move_to_head(self)
+	{
+		let s = self.list.sentinel.as_ptr();
+		self.relink_after(s);
+	}
+
+	pub fn move_to_tail(self)
+	{
+		let tail = self.list.sentinel.prev();
+		self.relink_after(tail);
+	}
+
+	fn relink_after(self, after: *mut Link<T>)
+	{
+		assert!(unsafe { (*self.link).is_linked() }, "the element was unlinked through its handle");
+		if after == self.link || unsafe { (*after).next() } == self.link {
+			return;
+		}
+		let l = unsafe { &mut *self.link };
+		l.splice_out();
+		insert_after(unsafe { &mut *after }, l);
+	}
+}
+
+impl<'a, T> Drop for HandleRef<'a, T>
+{
+	fn drop(&mut self)
+	{
+		Link::unview(self.link);
+	}
+}
+
+impl<'a, T> HeadEntry<'a, T>
+{
+	pub fn or_insert(self, v: T) -> &'a mut T
+	{
+		self.or_insert_with(|| v)
+	}
+
+	// the list owns an element pushed this way, there's no handle for it
+	pub fn or_insert_with<F: FnOnce() -> T>(self, f: F) -> &'a mut T
+	{
+		match self {
+			HeadEntry::Occupied(v) => v,
+			HeadEntry::Vacant(l) => {
+				let s: *mut Link<T> = &mut *l.sentinel;
+				l.link_after(s, f()).release();
+				l.peek_head_mut().unwrap()
+			}
+		}
+	}
+}
+
+impl<'a, T> HandleGuard<'a, T>
+{
+	pub fn get(&self) -> &T
+	{
+		unsafe { (*self.link).value.as_ref() }.expect("the element was unlinked through its handle")
+	}
+
+	pub fn get_mut(&mut self) -> &mut T
+	{
+		unsafe { (*self.link).value.as_mut() }.expect("the element was unlinked through its handle")
+	}
+
+	pub fn remove(self) -> T
+	{
+		let g = mem::ManuallyDrop::new(self);
+		let (list, link) = (g.list, g.link);
+		unsafe {
+			(*link).viewed = false;
+			(*list).remove_link(link)
+		}.expect("the element was unlinked through its handle")
+	}
+}
+
+impl<'a, T> Drop for HandleGuard<'a, T>
+{
+	fn drop(&mut self)
+	{
+		Link::unview(self.link);
+	}
+}
+
+impl<'a, T> Iterator for IterHandles<'a, T>
+{
+	type Item = HandleGuard<'a, T>;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		if self.next == self.end {
+			return None;
+		}
+		let link = self.next;
+		self.next = unsafe { (*link).next() };
+		unsafe { (*link).viewed = true };
+		Some(HandleGuard { list: self.list, link, marker: PhantomData })
+	}
+}
+
+impl<'a, T> FusedIterator for IterHandles<'a, T> {}
+
+// a cursor sits either on a real link or on the sentinel,
+// moving past either end lands on the sentinel and moving
+// once more wraps around to the other end
+impl<'a, T> Cursor<'a, T>
+{
+	pub fn move_next(&mut self)
+	{
+		self.current = unsafe { (*self.current).next() };
+	}
+
+	pub fn move_prev(&mut self)
+	{
+		self.current = unsafe { (*self.current).prev() };
+	}
+
+	pub fn current(&self) -> Option<&'a T>
+	{
+		unsafe { (*self.current).value.as_ref() }
+	}
+
+	pub fn list(&self) -> &'a List<T>
+	{
+		self.list
+	}
+}
+
+impl<'a, T> CursorMut<'a, T>
+{
+	pub fn move_next(&mut self)
+	{
+		self.current = unsafe { (*self.current).next() };
+	}
+
+	pub fn move_prev(&mut self)
+	{
+		self.current = unsafe { (*self.current).prev() };
+	}
+
+	pub fn current(&self) -> Option<&T>
+	{
+		unsafe { (*self.current).value.as_ref() }
+	}
+
+	pub fn current_mut(&mut self) -> Option<&mut T>
+	{
+		unsafe { (*self.current).value.as_mut() }
+	}
+
+	// removes the current element and moves on to the next one
+	pub fn remove_current(&mut self) -> Option<T>
+	{
+		let link = self.current;
+		unsafe { (*link).value.as_ref() }?;
+		self.current = unsafe { (*link).next() };
+		self.list.remove_link(link)
+	}
+
+	// on the sentinel this inserts at the head
+	pub fn insert_after(&mut self, e: T)
+	{
+		let link = self.current;
+		self.list.link_after(link, e).release();
+	}
+
+	// on the sentinel this inserts at the tail
+	pub fn insert_before(&mut self, e: T)
+	{
+		let link = unsafe { (*self.current).prev() };
+		self.list.link_after(link, e).release();
+	}
+}
+
+impl<'a, T, F: FnMut(&mut T) -> bool> Iterator for ExtractIf<'a, T, F>
+{
+	type Item = T;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		let s: *mut Link<T> = &mut *self.list.sentinel;
+		while self.next != s {
+			let link = self.next;
+			let l = unsafe { &mut *link };
+			self.next = l.next();
+			if (self.pred)(l.value.as_mut().unwrap()) {
+				return self.list.remove_link(link);
+			}
+		}
+		None
+	}
+}
+
+// walking from the tail is just the other end of the double ended iterators
+
+impl<'a, T> Iterator for IterRev<'a, T>
+{
+	type Item = &'a T;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		self.inner.next_back()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>)
+	{
+		self.inner.size_hint()
+	}
+}
+
+impl<'a, T> DoubleEndedIterator for IterRev<'a, T>
+{
+	fn next_back(&mut self) -> Option<Self::Item>
+	{
+		self.inner.next()
+	}
+}
+
+impl<'a, T> ExactSizeIterator for IterRev<'a, T> {}
+
+impl<'a, T> Iterator for IterRevMut<'a, T>
+{
+	type Item = &'a mut T;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		self.inner.next_back()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>)
+	{
+		self.inner.size_hint()
+	}
+}
+
+impl<'a, T> DoubleEndedIterator for IterRevMut<'a, T>
+{
+	fn next_back(&mut self) -> Option<Self::Item>
+	{
+		self.inner.next()
+	}
+}
+
+impl<'a, T> ExactSizeIterator for IterRevMut<'a, T> {}
+
+impl<T> Iterator for IntoIter<T>
+{
+	type Item = T;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		self.list.pop_head()
+	}
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T>
+{
+	fn next_back(&mut self) -> Option<Self::Item>
+	{
+		self.list.pop_tail()
+	}
+}
+
+impl<'a, T> Iterator for Chunks<'a, T>
+{
+	type Item = Vec<&'a T>;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		let chunk: Vec<_> = self.inner.by_ref().take(self.n).collect();
+		if chunk.is_empty() {
+			return None;
+		}
+		Some(chunk)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>)
+	{
+		let n = self.inner.len().div_ceil(self.n);
+		(n, Some(n))
+	}
+}
+
+impl<'a, T> ExactSizeIterator for Chunks<'a, T> {}
+
+impl<'a, T> Iterator for Windows<'a, T>
+{
+	type Item = Vec<&'a T>;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		if self.window.is_empty() {
+			self.window.extend(self.inner.by_ref().take(self.n));
+			if self.window.len() < self.n {
+				return None;
+			}
+		} else {
+			let v = self.inner.next()?;
+			self.window.remove(0);
+			self.window.push(v);
+		}
+		Some(self.window.clone())
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>)
+	{
+		let n = if self.window.is_empty() {
+			(self.inner.len() + 1).saturating_sub(self.n)
+		} else {
+			self.inner.len()
+		};
+		(n, Some(n))
+	}
+}
+
+impl<'a, T> ExactSizeIterator for Windows<'a, T> {}
+
+impl<'a, T> Iterator for StrideIter<'a, T>
+{
+	type Item = &'a T;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		if self.first {
+			self.first = false;
+			return self.inner.next();
+		}
+		self.inner.nth(self.step - 1)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>)
+	{
+		let n = if self.first {
+			self.inner.len().div_ceil(self.step)
+		} else {
+			self.inner.len() / self.step
+		};
+		(n, Some(n))
+	}
+}
+
+impl<'a, T> ExactSizeIterator for StrideIter<'a, T> {}
+
+impl<'a, T> Iterator for CycleIter<'a, T>
+{
+	type Item = &'a T;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		if self.list.is_empty() {
+			return None;
+		}
+		if self.next == self.list.sentinel.as_ptr() {
+			self.next = self.list.sentinel.next();
+		}
+		let link = unsafe { &*self.next };
+		self.next = link.next();
+		link.value.as_ref()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>)
+	{
+		if self.list.is_empty() {
+			return (0, Some(0));
+		}
+		(usize::MAX, None)
+	}
+}
+
+impl<'a, T, F: FnMut(&T, &T) -> bool> Iterator for GroupRuns<'a, T, F>
+{
+	type Item = Vec<&'a T>;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		let first = self.pending.take().or_else(|| self.inner.next())?;
+		let mut run = Vec::new();
+		run.push(first);
+		for v in self.inner.by_ref() {
+			if !(self.same)(run[run.len() - 1], v) {
+				self.pending = Some(v);
+				break;
+			}
+			run.push(v);
+		}
+		Some(run)
+	}
+}
+
+impl<'a, T> Iterator for Drain<'a, T>
+{
+	type Item = T;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		self.list.pop_head()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>)
+	{
+		(self.list.len(), Some(self.list.len()))
+	}
+}
+
+impl<'a, T> DoubleEndedIterator for Drain<'a, T>
+{
+	fn next_back(&mut self) -> Option<Self::Item>
+	{
+		self.list.pop_tail()
+	}
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> {}
+
+impl<'a, T> Drop for Drain<'a, T>
+{
+	fn drop(&mut self)
+	{
+		self.list.clear();
+	}
+}
+
+// once they hit the sentinel they keep returning None
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+impl<'a, T> FusedIterator for IterRev<'a, T> {}
+impl<'a, T> FusedIterator for IterRevMut<'a, T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+impl<'a, T> FusedIterator for Drain<'a, T> {}
+impl<'a, T> FusedIterator for Chunks<'a, T> {}
+impl<'a, T> FusedIterator for Windows<'a, T> {}
+impl<'a, T> FusedIterator for CycleIter<'a, T> {}
+impl<'a, T> FusedIterator for StrideIter<'a, T> {}
+impl<'a, T, F: FnMut(&T, &T) -> bool> FusedIterator for GroupRuns<'a, T, F> {}
+impl<'a, T, F: FnMut(&mut T) -> bool> FusedIterator for ExtractIf<'a, T, F> {}
+
+impl<T> IntoIterator for List<T>
+{
+	type Item = T;
+	type IntoIter = IntoIter<T>;
+
+	fn into_iter(self) -> Self::IntoIter
+	{
+		IntoIter { list: self }
+	}
+}
+
+impl<'a, T> IntoIterator for &'a List<T>
+{
+	type Item = &'a T;
+	type IntoIter = Iter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter
+	{
+		self.iter()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T>
+{
+	type Item = &'a mut T;
+	type IntoIter = IterMut<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter
+	{
+		self.iter_mut()
+	}
+}
+
+impl<T> FromIterator<T> for List<T>
+{
+	fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self
+	{
+		let mut l = List::new();
+		l.extend(iter);
+		l
+	}
+}
+
+impl<T> Extend<T> for List<T>
+{
+	fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I)
+	{
+		for e in iter {
+			// the list keeps the link, nobody gets a handle
+			self.push_tail_handle(e).release();
+		}
+	}
+}
+
+impl<T: Hash> Hash for List<T>
+{
+	fn hash<H: Hasher>(&self, state: &mut H)
+	{
+		state.write_usize(self.len());
+		for e in self.iter() {
+			e.hash(state);
+		}
+	}
+}
+
+impl<T> Default for List<T>
+{
+	fn default() -> Self
+	{
+		List::new()
+	}
+}
+
+impl<T> From<Vec<T>> for List<T>
+{
+	fn from(v: Vec<T>) -> Self
+	{
+		v.into_iter().collect()
+	}
+}
+
+impl<T> From<List<T>> for Vec<T>
+{
+	fn from(l: List<T>) -> Self
+	{
+		l.into_vec()
+	}
+}
+
+// walks from the nearer end, panics like Vec when out of range
+impl<T> Index<usize> for List<T>
+{
+	type Output = T;
+
+	fn index(&self, index: usize) -> &T
+	{
+		assert!(index < self.len(),
+			"index out of bounds: the len is {} but the index is {}", self.len(), index);
+		unsafe { &*self.link_at(index) }
+	}
+}
+
+impl<T> IndexMut<usize> for List<T>
+{
+	fn index_mut(&mut self, index: usize) -> &mut T
+	{
+		assert!(index < self.len(),
+			"index out of bounds: the len is {} but the index is {}", self.len(), index);
+		unsafe { (*self.link_at(index)).value.as_mut().unwrap() }
+	}
+}
+
+impl<T: Clone> Clone for List<T>
+{
+	fn clone(&self) -> Self
+	{
+		self.iter().cloned().collect()
+	}
+}
+
+impl<T: PartialEq> PartialEq for List<T>
+{
+	fn eq(&self, other: &Self) -> bool
+	{
+		self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+	}
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for List<T>
+{
+	fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error>
+	{
+		s.collect_seq(self.iter())
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for List<T>
+{
+	fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error>
+	{
+		struct ListVisitor<T>(PhantomData<T>);
+
+		impl<'de, T: serde::Deserialize<'de>> serde::de::Visitor<'de> for ListVisitor<T>
+		{
+			type Value = List<T>;
+
+			fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result
+			{
+				f.write_str("a sequence")
+			}
+
+			fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error>
+			{
+				let mut l = List::new();
+				while let Some(e) = seq.next_element::<T>()? {
+					l.push_tail_handle(e).release();
+				}
+				Ok(l)
+			}
+		}
+
+		d.deserialize_seq(ListVisitor(PhantomData))
+	}
+}
+
+impl<T> Link<T>
+{
+	fn empty() -> Link<T>
+	{
+		Link {
+			prev: None,
+			next: None,
+			value: None,
+			list: None,
+			handle: false,
+			viewed: false,
+			arena: false,
+		}
+	}
+
+	fn new(v: T) -> Link<T>
+	{
+		Link {
+			prev: None,
+			next: None,
+			value: Some(v),
+			list: None,
+			handle: false,
+			viewed: false,
+			arena: false,
+		}
+	}
+
+    fn is_linked(&self) -> bool
+    {
+        self.next.is_some()
+    }
+
+    // the neighbours of a linked link, asking an unlinked one is a bug
+    fn next(&self) -> *mut Link<T>
+    {
+        self.next.expect("link is not linked").as_ptr()
+    }
+
+    fn prev(&self) -> *mut Link<T>
+    {
+        self.prev.expect("link is not linked").as_ptr()
+    }
+
+    // just unlinks
+    // prev, next are cleared afterwards so calling
+    // this on an already unlinked link is a no-op
+    fn unlink(&mut self)
+    {
+        if !self.is_linked() {
+            return;
+        }
+        self.splice_out();
+        if let Some(list) = self.shared() {
+            list.len.set(list.len.get() - 1);
+        }
+        self.list = None;
+    }
+
+    // joins the neighbours and clears prev, next
+    // leaving the list this link counts against untouched
+    fn splice_out(&mut self)
+    {
+        let prev = unsafe { &mut *self.prev() };
+        let next = unsafe { &mut *self.next() };
+        next.prev = self.prev.take();
+        prev.next = self.next.take();
+    }
+
+    // the shared state of the list this link is in now,
+    // remembered so the forwarding is only followed once
+    fn shared(&mut self) -> Option<Rc<Shared<T>>>
+    {
+        let mut list = self.list.clone()?;
+        loop {
+            let next = list.forward.borrow().clone();
+            match next {
+                Some(next) => list = next,
+                None => break,
+            }
+        }
+        self.list = Some(list.clone());
+        Some(list)
+    }
+
+    // the view on this link is gone, if its handle was dropped
+    // and had unlinked it first nobody else is left to free it
+    fn unview(link: *mut Link<T>)
+    {
+        let l = unsafe { &mut *link };
+        l.viewed = false;
+        if !l.handle && !l.is_linked() {
+            drop(unsafe { Box::from_raw(link) });
+        }
+    }
+
+    // the sentinel of the list this link is in
+    fn sentinel(&mut self) -> *mut Link<T>
+    {
+        self.shared().expect("handle is not linked into a list").sentinel
+    }
+
+}
+impl<T> Shared<T>
+{
+	fn new(sentinel: *mut Link<T>) -> Rc<Self>
+	{
+		Rc::new(Shared {
+			len: Cell::new(0),
+			sentinel,
+			forward: RefCell::new(None),
+		})
+	}
+}
+
+impl<T> Arena<T>
+{
+	fn new(n: usize) -> Self
+	{
+		let mut links = Vec::with_capacity(n);
+		links.extend((0..n).map(|_| Link { arena: true, ..Link::empty() }));
+		Arena { links: NonNull::from(Box::leak(links.into_boxed_slice())) }
+	}
+
+	fn link(&self, i: usize) -> *mut Link<T>
+	{
+		assert!(i < self.links.len());
+		unsafe { (self.links.as_ptr() as *mut Link<T>).add(i) }
+	}
+}
+
+impl<T> Drop for Arena<T>
+{
+	fn drop(&mut self)
+	{
+		drop(unsafe { Box::from_raw(self.links.as_ptr()) });
+	}
+}
+
+impl<T> Handle<T>
+{
+    fn new(v: T) -> Self
+    {
+	    let mut link = Box::new(Link::new(v));
+	    link.handle = true;
+	    Handle::from_box(link)
+    }
+
+	fn from_box(link: Box<Link<T>>) -> Self
+	{
+		Handle(NonNull::from(Box::leak(link)))
+	}
+
+	fn as_ptr(&self) -> *mut Link<T>
+	{
+		self.0.as_ptr()
+	}
+
+	fn new_sentinel() -> Self 
+	{
+		let mut h = Box::new(
+			Link { 
+				prev: None,
+				next: None,
+				value: None,
+				list: None,
+				handle: true,
+				viewed: false,
+				arena: false,
+			}
+		);
+		h.prev = Some(NonNull::from(&mut *h));
+		h.next = h.prev;
+		Handle::from_box(h)
+	}
+    
+    fn into_inner(self) -> Option<T>
+    {
+        let mut h = self;
+        let link = &mut *h;
+        link.unlink();
+        link.value.take()
+    }
+
+	// gives up ownership of a linked link,
+	// from now on it's the list that frees it
+	fn release(self)
+	{
+		let mut h = self;
+		h.handle = false;
+		mem::forget(h);
+	}
+}
+
+impl<T: fmt::Debug> fmt::Debug for Link<T>
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Link {{ prev: {:?}, next: {:?}, value: {:?} }}", self.prev, self.next, self.value)
+	}
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T>
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_list().entries(self.iter()).finish()
+	}
+}
+
+impl<T> Drop for List<T>
+{
+	fn drop(&mut self)
+	{
+		// links still owned by handles are only unlinked here,
+		// their boxes go away when the handles drop
+		self.clear();
+	}
+}
+
+impl<T> Drop for Handle<T>
+{
+    fn drop(&mut self)
+    {
+        // the list never frees a link a handle owns,
+        // so this is the one place such a link goes away
+        let link: &mut Link<T> = self;
+        if link.viewed {
+            // a view still points here, a linked element stays in the
+            // list as if detached, an unlinked one is freed by the view
+            link.handle = false;
+            if !link.is_linked() {
+                link.value = None;
+            }
+            return;
+        }
+        link.unlink();
+        drop(unsafe { Box::from_raw(self.as_ptr()) });
+        // not sure if this matters
+        //println!("Drop");
+    }
+}
+
+impl<T> sealed::HasLink<T> for Handle<T>
+{
+	fn link(&self) -> *mut Link<T>
+	{
+		self.as_ptr()
+	}
+}
+
+impl<T> ListHandle<T> for Handle<T>
+{
+	fn unlink(self) -> T
+	{
+		// if you call it on the sentinel 
+		// you deserve to get what's coming
+		self.into_inner().unwrap()
+	}
+
+	// hands the handle back untouched when there is no value to unlink
+	fn try_unlink(self) -> Result<T, Self>
+	{
+		if self.try_as_ref().is_none() {
+			return Err(self);
+		}
+		Ok(self.unlink())
+	}
+
+	fn as_ref(&self) -> &T
+	{
+		self.value.as_ref().expect("as_ref on a sentinel or unlinked handle")
+	}
+
+	// None for a sentinel or once the list took the value back
+	fn try_as_ref(&self) -> Option<&T>
+	{
+		self.value.as_ref()
+	}
+
+	fn as_mut(&mut self) -> &mut T
+	{
+		self.value.as_mut().unwrap()
+	}
+
+	fn move_to_head(&mut self)
+	{
+		let s = self.sentinel();
+		if unsafe { (*s).next() } == self.as_ptr() {
+			return;
+		}
+		self.splice_out();
+		insert_after(unsafe { &mut *s }, self);
+	}
+
+	fn move_to_tail(&mut self)
+	{
+		let s = self.sentinel();
+		let tail = unsafe { (*s).prev() };
+		if tail == self.as_ptr() {
+			return;
+		}
+		self.splice_out();
+		insert_after(unsafe { &mut *(*s).prev() }, self);
+	}
+
+	// unlinking clears prev and next, a linked link always has both
+	fn is_linked(&self) -> bool
+	{
+		self.next.is_some()
+	}
+
+	// the sentinel has no value so the ends come out as None
+	fn peek_next(&self) -> Option<&T>
+	{
+		if !self.is_linked() {
+			return None;
+		}
+		unsafe { (*self.next()).value.as_ref() }
+	}
+
+	fn peek_prev(&self) -> Option<&T>
+	{
+		if !self.is_linked() {
+			return None;
+		}
+		unsafe { (*self.prev()).value.as_ref() }
+	}
+
+	// counts the links back to the sentinel, the only linked one without a value
+	fn index(&self) -> usize
+	{
+		assert!(self.is_linked(), "handle is not linked into a list");
+		let mut n = 0;
+		let mut link = self.prev();
+		while unsafe { (*link).value.is_some() } {
+			n += 1;
+			link = unsafe { (*link).prev() };
+		}
+		n
+	}
+
+	// keeps the element in the list but gives up the handle,
+	// a handle that was already unlinked just frees its link
+	fn detach(self)
+	{
+		if !self.is_linked() {
+			drop(self);
+		} else {
+			self.release();
+		}
+	}
+}
+
+impl<T> Deref for Link<T>
+{
+	type Target = T;
+	fn deref(&self) -> &Self::Target
+	{
+		// if you call it on the sentinel 
+		// you deserve to get what's coming
+		self.value.as_ref().unwrap()
+	}
+}
+
+impl<T> Deref for Handle<T>
+{
+	type Target = Link<T>;
+	fn deref(&self) -> &Self::Target
+	{
+		unsafe { self.0.as_ref() }
+	}
+}
+
+impl<T> DerefMut for Handle<T>
+{
+	fn deref_mut(&mut self) -> &mut Self::Target
+	{
+		unsafe { self.0.as_mut() }
+	}
+}
+
+// h must not be linked anywhere (prev, next None), reinserting a live
+// link would corrupt both chains so debug builds check for it
+fn insert_after<T>(after: &mut Link<T>, h: &mut Link<T>)
+{
+	debug_assert!(!h.is_linked() && h.prev.is_none(),
+		"inserting a link that is still linked");
+	debug_assert!(after.is_linked() && after.prev.is_some(),
+		"inserting after a link that is not linked");
+
+	let n = after.next;
+
+	h.prev = Some(NonNull::from(&mut *after));
+	h.next = n;
+	
+	after.next = Some(NonNull::from(&mut *h));
+	
+	let n = unsafe { &mut *h.next() };
+	n.prev = Some(NonNull::from(&mut *h));
+}
+
+#[allow(dead_code)]
+#[cfg(test)]
+fn debug_print<T: fmt::Debug>(s: &mut Handle<T>)
+{
+	let mut h = s.next();
+	while h != &mut **s {
+		let n = unsafe { &*h };
+		println!("{:?}", n);
+		h = n.next();
+	}
+}
+
+#[cfg(test)]
+#[test]
+fn link_unlink()
+{
+	let mut s = Handle::new_sentinel();
+	let mut h1 = Handle::new(1);
+	let mut h2 = Handle::new(2);
+	let mut h3 = Handle::new(3);
+	
+	insert_after(&mut s,  &mut h1);
+	insert_after(&mut h1, &mut h2);
+	insert_after(&mut h2, &mut h3);
+
+	assert_eq!(unsafe {&*s.next()}, &*h1);
+	assert_eq!(unsafe {&*h1.prev()}, &*s);
+
+	assert_eq!(unsafe {&*h1.next()}, &*h2);
+	assert_eq!(unsafe {&*h2.prev()}, &*h1);
+	
+	assert_eq!(unsafe {&*h2.next()}, &*h3);
+	assert_eq!(unsafe {&*h3.prev()}, &*h2);
+	
+	assert_eq!(unsafe {&*h3.next()}, &*s);
+	assert_eq!(unsafe {&*s.prev()}, &*h3);
+
+	h2.into_inner();
+
+	assert_eq!(unsafe {&*s.next()}, &*h1);
+	assert_eq!(unsafe {&*h1.prev()}, &*s);
+	
+	assert_eq!(unsafe {&*h1.next()}, &*h3);
+	assert_eq!(unsafe {&*h3.prev()}, &*h1);
+	
+	assert_eq!(unsafe {&*h3.next()}, &*s);
+	assert_eq!(unsafe {&*s.prev()}, &*h3);
+	
+	let mut h2 = Handle::new(2);
+	insert_after(&mut h1, &mut h2);
+	
+	h1.into_inner();
+	assert_eq!(unsafe {&*s.next()}, &*h2);
+	h2.into_inner();
+	assert_eq!(unsafe {&*s.next()}, &*h3);
+	assert_eq!(unsafe {&*s.prev()}, &*h3);
+	assert_eq!(unsafe {&*h3.prev()}, &*s);
+	assert_eq!(unsafe {&*h3.next()}, &*s);
+	h3.into_inner();
+	assert_eq!(unsafe {&*s.prev()}, &*s);
+	assert_eq!(unsafe {&*s.next()}, &*s);
+	
+	s.into_inner();
+}
+
+#[cfg(test)]
+#[test]
+fn iter_test()
+{
+	let l = &mut List::new();
+	let h1 = l.push_head(1);
+    {
+	let _h2 = l.push_tail(2);
+    }
+    let h3 = l.push_tail(3);
+
+	let mut i = l.iter();
+	assert_eq!(Some(&1), i.next());
+	//assert_eq!(Some(&2), i.next());
+	assert_eq!(Some(&3), i.next());
+	assert_eq!(None, i.next());
+
+    h1.unlink();
+    //h2.unlink();
+    h3.unlink();
+}
+
+#[cfg(test)]
+#[test]
+fn iter_mut_test()
+{
+	let l = &mut List::new();
+	let h1 = l.push_head(1);
+	let h2 = l.push_tail(2);
+	let h3 = l.push_tail(3);
+
+	{
+	let mut i = l.iter_mut();
+	assert_eq!(Some(&mut 1), i.next());
+	assert_eq!(Some(&mut 2), i.next());
+	assert_eq!(Some(&mut 3), i.next());
+	assert_eq!(None, i.next());
+	}
+
+	l.iter_mut().fold(3, |i, v| {
+		*v = i;
+		i - 1
+	});
+
+	{
+	let mut i = l.iter_mut();
+	assert_eq!(Some(&mut 3), i.next());
+	assert_eq!(Some(&mut 2), i.next());
+	assert_eq!(Some(&mut 1), i.next());
+	assert_eq!(None, i.next());
+	}
+	
+	assert_eq!(&3, h1.as_ref());
+	assert_eq!(&2, h2.as_ref());
+	assert_eq!(&1, h3.as_ref());
+}
+
+#[allow(dead_code)]
+#[cfg(all(test, feature = "std"))]
+//#[test]
+fn test_drop()
+{
+    let line = &mut String::new();
+    
+    let _ = std::io::stdin().read_line(line);
+	
+    let mut s = Handle::new_sentinel();
+    let mut vec = vec![];
+
+    for _ in 1..100000 {
+        let mut h = Handle::new(vec![0u32;1000]);
+        insert_after(&mut s,  &mut h);
+        vec.push(h);
+    }
+    
+    let _ = std::io::stdin().read_line(line);
+
+    while !vec.is_empty() {
+        vec.pop().unwrap().into_inner();
+    }
+    
+    let _ = std::io::stdin().read_line(line);
+}
+
+#[cfg(test)]
+#[test]
+fn len_test()
+{
+	let l = &mut List::new();
+	assert_eq!(0, l.len());
+	assert!(l.is_empty());
+
+	let h1 = l.push_head(1);
+	let h2 = l.push_tail(2);
+	{
+		let _h3 = l.push_tail(3);
+		assert_eq!(3, l.len());
+	}
+	assert_eq!(2, l.len());
+	assert!(!l.is_empty());
+
+	h1.unlink();
+	assert_eq!(1, l.len());
+	h2.unlink();
+	assert_eq!(0, l.len());
+	assert!(l.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn double_ended_iter_test()
+{
+	let l = &mut List::new();
+	let _h: Vec<_> = (1..6).map(|i| l.push_tail(i)).collect();
+
+	let mut i = l.iter();
+	assert_eq!(Some(&1), i.next());
+	assert_eq!(Some(&5), i.next_back());
+	assert_eq!(Some(&2), i.next());
+	assert_eq!(Some(&4), i.next_back());
+	assert_eq!(Some(&3), i.next());
+	assert_eq!(None, i.next_back());
+	assert_eq!(None, i.next());
+
+	let r: Vec<_> = l.iter().rev().collect();
+	assert_eq!(vec![&5, &4, &3, &2, &1], r);
+
+	{
+	let mut i = l.iter_mut();
+	assert_eq!(Some(&mut 5), i.next_back());
+	assert_eq!(Some(&mut 1), i.next());
+	assert_eq!(Some(&mut 4), i.next_back());
+	assert_eq!(Some(&mut 2), i.next());
+	assert_eq!(Some(&mut 3), i.next_back());
+	assert_eq!(None, i.next());
+	assert_eq!(None, i.next_back());
+	}
+
+	let e = &mut List::<i32>::new();
+	assert_eq!(None, e.iter().next_back());
+	assert_eq!(None, e.iter_mut().next_back());
+}
+
+#[cfg(test)]
+#[test]
+fn pop_test()
+{
+	let l = &mut List::new();
+	assert_eq!(None, l.pop_head());
+	assert_eq!(None, l.pop_tail());
+
+	let _h1 = l.push_tail(1);
+	let _h2 = l.push_tail(2);
+	let _h3 = l.push_tail(3);
+	assert_eq!(Some(1), l.pop_head());
+	assert_eq!(Some(2), l.pop_head());
+	assert_eq!(Some(3), l.pop_head());
+	assert_eq!(None, l.pop_head());
+	assert!(l.is_empty());
+
+	let _h1 = l.push_tail(1);
+	let _h2 = l.push_tail(2);
+	let _h3 = l.push_tail(3);
+	assert_eq!(Some(3), l.pop_tail());
+	assert_eq!(Some(2), l.pop_tail());
+	assert_eq!(Some(1), l.pop_tail());
+	assert_eq!(None, l.pop_tail());
+	assert_eq!(None, l.peek_head());
+	assert!(l.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn into_iter_test()
+{
+	let mut l = List::new();
+	let _h: Vec<_> = (1..6).map(|i| l.push_tail(i)).collect();
+
+	for (i, v) in (&l).into_iter().enumerate() {
+		assert_eq!(i + 1, *v);
+	}
+	for v in &mut l {
+		*v *= 10;
+	}
+
+	let v: Vec<_> = l.into_iter().collect();
+	assert_eq!(vec![10, 20, 30, 40, 50], v);
+
+	let mut l = List::new();
+	let _h: Vec<_> = (1..6).map(|i| l.push_tail(vec![i])).collect();
+	let mut i = l.into_iter();
+	assert_eq!(Some(vec![1]), i.next());
+	assert_eq!(Some(vec![5]), i.next_back());
+	drop(i);
+}
+
+#[cfg(test)]
+#[test]
+fn from_iter_extend_test()
+{
+	let mut l: List<_> = (1..4).collect();
+	assert_eq!(3, l.len());
+	assert_eq!(vec![&1, &2, &3], l.iter().collect::<Vec<_>>());
+
+	l.extend(vec![4, 5]);
+	assert_eq!(5, l.len());
+	assert_eq!(&5, l.peek_tail().unwrap());
+
+	assert_eq!(Some(1), l.pop_head());
+	assert_eq!(Some(5), l.pop_tail());
+	assert_eq!(vec![2, 3, 4], l.into_iter().collect::<Vec<_>>());
+}
+
+#[cfg(test)]
+#[test]
+fn clear_test()
+{
+	let mut l: List<_> = (0..10u32).map(|i| vec![i; 100]).collect();
+	let h = l.push_head(vec![42]);
+	assert_eq!(11, l.len());
+
+	l.clear();
+	assert!(l.is_empty());
+	assert_eq!(None, l.peek_head());
+	assert_eq!(None, l.peek_tail());
+	assert_eq!(None, l.iter().next());
+	drop(h);
+
+	l.extend(vec![vec![1u32], vec![2]]);
+	assert_eq!(2, l.len());
+	assert_eq!(&vec![1], l.peek_head().unwrap());
+	l.clear();
+	l.clear();
+	assert!(l.is_empty());
+}
+
+#[cfg(test)]
+struct DropCounter<'a>(&'a Cell<usize>);
+
+#[cfg(test)]
+impl<'a> Drop for DropCounter<'a>
+{
+	fn drop(&mut self)
+	{
+		self.0.set(self.0.get() + 1);
+	}
+}
+
+#[cfg(test)]
+#[test]
+fn list_drop_test()
+{
+	let drops = Cell::new(0);
+	let l: List<_> = (0..10000).map(|_| DropCounter(&drops)).collect();
+	assert_eq!(10000, l.len());
+	drop(l);
+	assert_eq!(10000, drops.get());
+
+	let drops = Cell::new(0);
+	let mut l: List<_> = (0..10).map(|_| DropCounter(&drops)).collect();
+	let h = l.push_tail(DropCounter(&drops));
+	let mut i = l.into_iter();
+	i.next();
+	assert_eq!(1, drops.get());
+	drop(i);
+	assert_eq!(11, drops.get());
+	drop(h);
+	assert_eq!(11, drops.get());
+}
+
+#[cfg(test)]
+#[test]
+fn insert_after_before_test()
+{
+	let l = &mut List::new();
+	let h1 = l.push_tail(1);
+	let h3 = l.push_tail(3);
+
+	let h2 = l.insert_after(&h1, 2);
+	assert_eq!(vec![&1, &2, &3], l.iter().collect::<Vec<_>>());
+
+	let h0 = l.insert_before(&h1, 0);
+	let h4 = l.insert_after(&h3, 4);
+	assert_eq!(vec![&0, &1, &2, &3, &4], l.iter().collect::<Vec<_>>());
+	assert_eq!(5, l.len());
+
+	assert_eq!(2, h2.unlink());
+	assert_eq!(vec![&0, &1, &3, &4], l.iter().collect::<Vec<_>>());
+	assert_eq!(&0, h0.as_ref());
+	assert_eq!(&4, l.peek_tail().unwrap());
+	drop(h4);
+	assert_eq!(3, l.len());
+}
+
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "handle is not linked into this list")]
+fn insert_after_foreign_handle_test()
+{
+	let a = &mut List::new();
+	let b = &mut List::new();
+	let h = a.push_tail(1);
+	b.insert_after(&h, 2);
+}
+
+#[cfg(test)]
+#[test]
+fn move_to_head_tail_test()
+{
+	let l = &mut List::new();
+	let mut h1 = l.push_tail(1);
+	let mut h2 = l.push_tail(2);
+	let mut h3 = l.push_tail(3);
+
+	h2.move_to_head();
+	assert_eq!(&2, l.peek_head().unwrap());
+	assert_eq!(vec![&2, &1, &3], l.iter().collect::<Vec<_>>());
+
+	h2.move_to_head();
+	assert_eq!(vec![&2, &1, &3], l.iter().collect::<Vec<_>>());
+
+	h2.move_to_tail();
+	assert_eq!(vec![&1, &3, &2], l.iter().collect::<Vec<_>>());
+	h2.move_to_tail();
+	h3.move_to_tail();
+	h1.move_to_tail();
+	assert_eq!(vec![&2, &3, &1], l.iter().collect::<Vec<_>>());
+	assert_eq!(vec![&1, &3, &2], l.iter().rev().collect::<Vec<_>>());
+	assert_eq!(3, l.len());
+
+	assert_eq!(3, h3.unlink());
+	assert_eq!(vec![&2, &1], l.iter().collect::<Vec<_>>());
+}
+
+#[cfg(test)]
+#[test]
+fn clone_test()
+{
+	let mut l: List<_> = vec![vec![1], vec![2], vec![3]].into_iter().collect();
+	let c = l.clone();
+	assert_eq!(3, c.len());
+
+	l.peek_head_mut().unwrap().push(10);
+	assert_eq!(Some(&vec![1, 10]), l.peek_head());
+	assert_eq!(Some(&vec![1]), c.peek_head());
+
+	l.clear();
+	assert_eq!(vec![&vec![1], &vec![2], &vec![3]], c.iter().collect::<Vec<_>>());
+}
+
+#[cfg(test)]
+#[test]
+fn eq_test()
+{
+	let a: List<_> = (1..4).collect();
+	let b: List<_> = (1..4).collect();
+	assert!(a == b);
+	assert!(List::<i32>::new() == List::new());
+
+	let c: List<_> = vec![1, 2, 4].into_iter().collect();
+	assert!(a != c);
+
+	let d: List<_> = (1..3).collect();
+	assert!(a != d);
+	assert!(d != a);
+	assert!(a != List::new());
+}
+
+#[cfg(test)]
+#[test]
+fn debug_test()
+{
+	let l: List<_> = (1..4).collect();
+	assert_eq!("[1, 2, 3]", format!("{:?}", l));
+	assert_eq!("[]", format!("{:?}", List::<i32>::new()));
+}
+
+#[cfg(test)]
+#[test]
+fn contains_test()
+{
+	let l: List<_> = (1..4).collect();
+	assert!(l.contains(&1));
+	assert!(l.contains(&3));
+	assert!(!l.contains(&4));
+	assert!(!List::new().contains(&1));
+}
+
+#[cfg(test)]
+#[test]
+fn find_test()
+{
+	let mut l: List<_> = (1..6).collect();
+	assert_eq!(Some(&3), l.find(|v| *v == 3));
+	assert_eq!(Some(&2), l.find(|v| v % 2 == 0));
+	assert_eq!(None, l.find(|v| *v > 5));
+
+	*l.find_mut(|v| *v == 3).unwrap() += 10;
+	assert_eq!(vec![&1, &2, &13, &4, &5], l.iter().collect::<Vec<_>>());
+	assert_eq!(None, l.find_mut(|v| *v == 3));
+
+	let mut e = List::<i32>::new();
+	assert_eq!(None, e.find(|_| true));
+	assert_eq!(None, e.find_mut(|_| true));
+}
+
+#[cfg(test)]
+#[test]
+fn exact_size_iter_test()
+{
+	let mut l: List<_> = (1..6).collect();
+	assert_eq!(5, l.iter().len());
+	assert_eq!((5, Some(5)), l.iter().size_hint());
+
+	let mut i = l.iter();
+	i.next();
+	i.next_back();
+	assert_eq!(3, i.len());
+	assert_eq!(3, i.count());
+
+	let mut i = l.iter_mut();
+	assert_eq!(5, i.len());
+	i.next();
+	assert_eq!(4, i.len());
+	assert_eq!(4, i.count());
+
+	assert_eq!(0, List::<i32>::new().iter().len());
+}
+
+#[cfg(test)]
+#[test]
+fn cursor_test()
+{
+	let l: List<_> = (1..4).collect();
+	let mut c = l.cursor_head();
+	assert_eq!(Some(&1), c.current());
+	c.move_next();
+	c.move_next();
+	assert_eq!(Some(&3), c.current());
+	c.move_next();
+	assert_eq!(None, c.current());
+	c.move_next();
+	assert_eq!(Some(&1), c.current());
+	c.move_prev();
+	c.move_prev();
+	assert_eq!(Some(&3), c.current());
+
+	let c = l.cursor_tail();
+	assert_eq!(Some(&3), c.current());
+	assert_eq!(None, List::<i32>::new().cursor_head().current());
+}
+
+#[cfg(test)]
+#[test]
+fn cursor_mut_test()
+{
+	let mut l: List<_> = (1..6).collect();
+	{
+		let mut c = l.cursor_head_mut();
+		c.move_next();
+		c.move_next();
+		assert_eq!(Some(3), c.remove_current());
+		assert_eq!(Some(&4), c.current());
+		*c.current_mut().unwrap() = 40;
+		c.insert_after(45);
+		c.insert_before(35);
+		c.move_next();
+		assert_eq!(Some(&45), c.current());
+		c.move_next();
+		assert_eq!(Some(5), c.remove_current());
+		assert_eq!(None, c.current());
+		assert_eq!(None, c.remove_current());
+		c.insert_after(0);
+		c.insert_before(6);
+	}
+	assert_eq!(vec![&0, &1, &2, &35, &40, &45, &6], l.iter().collect::<Vec<_>>());
+	assert_eq!(7, l.len());
+
+	{
+		let mut c = l.cursor_tail_mut();
+		while c.remove_current().is_some() {
+			c.move_prev();
+		}
+	}
+	assert!(l.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn retain_test()
+{
+	let mut l: List<_> = (1..6).collect();
+	l.retain(|v| v % 2 == 0);
+	assert_eq!(vec![&2, &4], l.iter().collect::<Vec<_>>());
+	assert_eq!(2, l.len());
+
+	let mut l: List<_> = (1..6).collect();
+	l.retain(|v| *v != 1 && *v != 5);
+	assert_eq!(vec![&2, &3, &4], l.iter().collect::<Vec<_>>());
+	assert_eq!(&2, l.peek_head().unwrap());
+	assert_eq!(&4, l.peek_tail().unwrap());
+
+	let drops = Cell::new(0);
+	let mut l: List<_> = (0..5).map(|_| DropCounter(&drops)).collect();
+	l.retain(|_| false);
+	assert!(l.is_empty());
+	assert_eq!(5, drops.get());
+}
+
+#[cfg(test)]
+#[test]
+fn append_test()
+{
+	let mut a: List<_> = (1..4).collect();
+	let mut b: List<_> = (4..7).collect();
+	a.append(&mut b);
+	assert_eq!(vec![&1, &2, &3, &4, &5, &6], a.iter().collect::<Vec<_>>());
+	assert_eq!(vec![&6, &5, &4, &3, &2, &1], a.iter().rev().collect::<Vec<_>>());
+	assert_eq!(6, a.len());
+	assert!(b.is_empty());
+	assert_eq!(None, b.iter().next());
+
+	a.append(&mut b);
+	assert_eq!(6, a.len());
+
+	let mut e = List::new();
+	e.append(&mut a);
+	assert_eq!(6, e.len());
+	assert!(a.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn append_handles_test()
+{
+	let a = &mut List::new();
+	let b = &mut List::new();
+	let _h1 = a.push_tail(1);
+	let h2 = b.push_tail(2);
+	let mut h3 = b.push_tail(3);
+	a.append(b);
+
+	let _h4 = b.push_tail(4);
+	assert_eq!(1, b.len());
+	assert_eq!(3, a.len());
+
+	h3.move_to_head();
+	assert_eq!(vec![&3, &1, &2], a.iter().collect::<Vec<_>>());
+	let h5 = a.insert_after(&h2, 5);
+	assert_eq!(4, a.len());
+
+	assert_eq!(2, h2.unlink());
+	drop(h5);
+	assert_eq!(2, a.len());
+	assert_eq!(1, b.len());
+
+	let c = &mut List::new();
+	c.append(a);
+	drop(h3);
+	assert_eq!(1, c.len());
+	assert_eq!(0, a.len());
+}
+
+#[cfg(test)]
+#[test]
+fn split_after_test()
+{
+	let l = &mut List::new();
+	let h1 = l.push_tail(1);
+	let h2 = l.push_tail(2);
+	let mut h3 = l.push_tail(3);
+	let h4 = l.push_tail(4);
+
+	let mut r = l.split_after(&h4);
+	assert!(r.is_empty());
+	assert_eq!(4, l.len());
+
+	let r2 = l.split_after(&h2);
+	assert_eq!(vec![&1, &2], l.iter().collect::<Vec<_>>());
+	assert_eq!(vec![&3, &4], r2.iter().collect::<Vec<_>>());
+	assert_eq!(2, l.len());
+	assert_eq!(2, r2.len());
+
+	h3.move_to_tail();
+	assert_eq!(vec![&4, &3], r2.iter().collect::<Vec<_>>());
+	drop(h4);
+	assert_eq!(1, r2.len());
+
+	r = l.split_after(&h1);
+	assert_eq!(vec![&1], l.iter().collect::<Vec<_>>());
+	assert_eq!(vec![&2], r.iter().collect::<Vec<_>>());
+	assert_eq!(2, h2.unlink());
+	assert!(r.is_empty());
+	assert_eq!(1, l.len());
+}
+
+#[cfg(test)]
+#[test]
+fn reverse_test()
+{
+	let l = &mut List::new();
+	l.reverse();
+	assert!(l.is_empty());
+
+	let h1 = l.push_tail(1);
+	l.reverse();
+	assert_eq!(vec![&1], l.iter().collect::<Vec<_>>());
+
+	let h2 = l.push_tail(2);
+	let h3 = l.push_tail(3);
+	l.reverse();
+	assert_eq!(vec![&3, &2, &1], l.iter().collect::<Vec<_>>());
+	assert_eq!(vec![&1, &2, &3], l.iter().rev().collect::<Vec<_>>());
+	assert_eq!(&3, l.peek_head().unwrap());
+	assert_eq!(&1, l.peek_tail().unwrap());
+	assert_eq!(&2, h2.as_ref());
+
+	assert_eq!(2, h2.unlink());
+	assert_eq!(vec![&3, &1], l.iter().collect::<Vec<_>>());
+	assert_eq!(1, h1.unlink());
+	assert_eq!(3, h3.unlink());
+	assert!(l.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn get_test()
+{
+	let mut l: List<_> = (1..4).collect();
+	assert_eq!(Some(&1), l.get(0));
+	assert_eq!(Some(&3), l.get(2));
+	assert_eq!(None, l.get(3));
+	assert_eq!(None, l.get(100));
+
+	*l.get_mut(1).unwrap() = 20;
+	assert_eq!(Some(&20), l.get(1));
+	assert_eq!(None, l.get_mut(3));
+
+	let mut e = List::<i32>::new();
+	assert_eq!(None, e.get(0));
+	assert_eq!(None, e.get_mut(0));
+}
+
+#[cfg(test)]
+#[test]
+fn extract_if_test()
+{
+	let mut l: List<_> = (1..8).collect();
+	let odd: Vec<_> = l.extract_if(|v| *v % 2 == 1).collect();
+	assert_eq!(vec![1, 3, 5, 7], odd);
+	assert_eq!(vec![&2, &4, &6], l.iter().collect::<Vec<_>>());
+	assert_eq!(3, l.len());
+
+	{
+		let mut i = l.extract_if(|v| { *v *= 10; true });
+		assert_eq!(Some(20), i.next());
+	}
+	assert_eq!(vec![&4, &6], l.iter().collect::<Vec<_>>());
+
+	assert_eq!(0, l.extract_if(|_| false).count());
+	assert_eq!(2, l.extract_if(|_| true).count());
+	assert!(l.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn swap_test()
+{
+	let l = &mut List::new();
+	let h: Vec<_> = (1..6).map(|i| l.push_tail(i)).collect();
+
+	l.swap(&h[0], &h[3]);
+	assert_eq!(vec![&4, &2, &3, &1, &5], l.iter().collect::<Vec<_>>());
+	assert_eq!(vec![&5, &1, &3, &2, &4], l.iter().rev().collect::<Vec<_>>());
+
+	l.swap(&h[1], &h[2]);
+	assert_eq!(vec![&4, &3, &2, &1, &5], l.iter().collect::<Vec<_>>());
+	l.swap(&h[4], &h[0]);
+	assert_eq!(vec![&4, &3, &2, &5, &1], l.iter().collect::<Vec<_>>());
+	assert_eq!(vec![&1, &5, &2, &3, &4], l.iter().rev().collect::<Vec<_>>());
+
+	l.swap(&h[2], &h[2]);
+	assert_eq!(vec![&4, &3, &2, &5, &1], l.iter().collect::<Vec<_>>());
+	assert_eq!(&1, h[0].as_ref());
+	assert_eq!(5, l.len());
+}
+
+#[cfg(test)]
+#[test]
+fn rotate_test()
+{
+	let mut l: List<_> = (1..6).collect();
+	l.rotate_left(2);
+	assert_eq!(vec![&3, &4, &5, &1, &2], l.iter().collect::<Vec<_>>());
+	l.rotate_right(2);
+	assert_eq!(vec![&1, &2, &3, &4, &5], l.iter().collect::<Vec<_>>());
+	l.rotate_right(2);
+	assert_eq!(vec![&4, &5, &1, &2, &3], l.iter().collect::<Vec<_>>());
+	assert_eq!(vec![&3, &2, &1, &5, &4], l.iter().rev().collect::<Vec<_>>());
+
+	l.rotate_left(5);
+	assert_eq!(vec![&4, &5, &1, &2, &3], l.iter().collect::<Vec<_>>());
+	l.rotate_left(12);
+	assert_eq!(vec![&1, &2, &3, &4, &5], l.iter().collect::<Vec<_>>());
+	l.rotate_right(9);
+	assert_eq!(vec![&2, &3, &4, &5, &1], l.iter().collect::<Vec<_>>());
+	assert_eq!(5, l.len());
+
+	let mut e = List::<i32>::new();
+	e.rotate_left(3);
+	e.rotate_right(3);
+	assert!(e.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn default_test()
+{
+	let l = List::<i32>::default();
+	assert!(l.is_empty());
+	assert_eq!(None, l.peek_head());
+}
+
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
+#[cfg(all(test, feature = "serde"))]
+#[test]
+fn serde_test()
+{
+	let l: List<_> = (1..4).collect();
+	let json = serde_json::to_string(&l).unwrap();
+	assert_eq!("[1,2,3]", json);
+
+	let mut d: List<i32> = serde_json::from_str(&json).unwrap();
+	assert_eq!(l, d);
+	*d.peek_head_mut().unwrap() = 10;
+	assert_eq!(Some(&1), l.peek_head());
+
+	let e: List<i32> = serde_json::from_str("[]").unwrap();
+	assert!(e.is_empty());
+	assert!(serde_json::from_str::<List<i32>>("{}").is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn head_tail_handle_test()
+{
+	let mut l: List<_> = (1..5).collect();
+	let h = l.head_handle().unwrap();
+	assert_eq!(&1, h.get());
+	assert_eq!(1, h.unlink());
+
+	let mut h = l.tail_handle().unwrap();
+	*h.get_mut() = 40;
+	assert_eq!(40, h.unlink());
+	assert_eq!(vec![&2, &3], l.iter().collect::<Vec<_>>());
+	assert_eq!(2, l.len());
+
+	// the push handle outlives the element removed through the view
+	let p = l.push_head(0);
+	assert_eq!(0, l.head_handle().unwrap().unlink());
+	assert_eq!(vec![&2, &3], l.iter().collect::<Vec<_>>());
+	drop(p);
+	assert_eq!(2, l.len());
+
+	l.clear();
+	assert!(l.head_handle().is_none());
+	assert!(l.tail_handle().is_none());
+}
+
+#[cfg(test)]
+#[test]
+fn try_as_ref_test()
+{
+	let l = &mut List::new();
+	let h = l.push_tail(1);
+	assert_eq!(&1, h.as_ref());
+	assert_eq!(Some(&1), h.try_as_ref());
+
+	assert_eq!(Some(1), l.pop_head());
+	assert_eq!(None, h.try_as_ref());
+
+	let s = Handle::<i32>::new_sentinel();
+	assert_eq!(None, s.try_as_ref());
+}
+
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "as_ref on a sentinel or unlinked handle")]
+fn as_ref_sentinel_test()
+{
+	let s = Handle::<i32>::new_sentinel();
+	s.as_ref();
+}
+
+#[cfg(test)]
+#[test]
+fn as_mut_test()
+{
+	let l = &mut List::new();
+	let mut h = l.push_head(1);
+	*h.as_mut() += 10;
+	assert_eq!(Some(&11), l.peek_head());
+	assert_eq!(&11, h.as_ref());
+}
+
+#[cfg(test)]
+#[test]
+fn try_unlink_test()
+{
+	let l = &mut List::new();
+	let h1 = l.push_tail(1);
+	let h2 = l.push_tail(2);
+	assert_eq!(Ok(1), h1.try_unlink().map_err(|_| ()));
+	assert_eq!(1, l.len());
+
+	assert_eq!(Some(2), l.pop_head());
+	assert!(h2.try_unlink().is_err());
+
+	let s = Handle::<i32>::new_sentinel();
+	let s = match s.try_unlink() {
+		Ok(_) => panic!("unlinked a sentinel"),
+		Err(s) => s,
+	};
+	assert_eq!(s.as_ptr(), s.next());
+	assert_eq!(s.as_ptr(), s.prev());
+}
+
+#[cfg(test)]
+#[test]
+fn list_macro_test()
+{
+	let mut l = List::new();
+	l.extend(vec![1, 2, 3]);
+	assert_eq!(l, list![1, 2, 3]);
+	assert_eq!(l, list![1, 2, 3,]);
+	assert_eq!(List::<i32>::new(), list![]);
+
+	let z = list![0; 4];
+	assert_eq!(vec![&0, &0, &0, &0], z.iter().collect::<Vec<_>>());
+	assert!(list![vec![1]; 0].is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn push_tail_at_test()
+{
+	let l = &mut List::new();
+	let (h1, i1) = l.push_tail_at(1);
+	let (_h2, i2) = l.push_tail_at(2);
+	let (_h3, i3) = l.push_tail_at(3);
+	assert_eq!((0, 1, 2), (i1, i2, i3));
+	assert_eq!(Some(&2), l.get(i2));
+	assert_eq!(Some(&3), l.get(i3));
+
+	h1.unlink();
+	let (_h4, i4) = l.push_tail_at(4);
+	assert_eq!(2, i4);
+	assert_eq!(Some(&4), l.get(i4));
+}
+
+#[cfg(test)]
+#[test]
+// the length lives in a Cell so a handle could change a key,
+// none are handed out here
+#[allow(clippy::mutable_key_type)]
+fn hash_test()
+{
+	use std::collections::HashSet;
+
+	let mut set = HashSet::new();
+	set.insert(list![1, 2, 3]);
+	set.insert(list![1, 2, 3]);
+	assert_eq!(1, set.len());
+	set.insert(list![1, 2]);
+	set.insert(List::new());
+	set.insert(List::new());
+	assert_eq!(3, set.len());
+	assert!(set.contains(&list![1, 2]));
+}
+
+#[cfg(all(test, debug_assertions))]
+#[test]
+#[should_panic(expected = "inserting a link that is still linked")]
+fn reinsert_live_link_test()
+{
+	let mut s = Handle::new_sentinel();
+	let mut h1 = Handle::new(1);
+	let mut h2 = Handle::new(2);
+	insert_after(&mut s, &mut h1);
+	insert_after(&mut h1, &mut h2);
+	insert_after(&mut s, &mut h2);
+}
+
+#[cfg(all(test, debug_assertions))]
+#[test]
+#[should_panic(expected = "inserting after a link that is not linked")]
+fn insert_after_unlinked_test()
+{
+	let mut h1 = Handle::new(1);
+	let mut h2 = Handle::new(2);
+	insert_after(&mut h1, &mut h2);
+}
+
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "handle is not linked into this list")]
+fn swap_foreign_handle_test()
+{
+	let a = &mut List::new();
+	let b = &mut List::new();
+	let ha = a.push_tail(1);
+	let hb = b.push_tail(2);
+	a.swap(&ha, &hb);
+}
+
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "handle is not linked into this list")]
+fn insert_after_popped_handle_test()
+{
+	let l = &mut List::new();
+	let h = l.push_tail(1);
+	l.pop_tail();
+	l.insert_after(&h, 2);
+}
+
+#[cfg(test)]
+#[test]
+fn head_tail_handle_ownership_test()
+{
+	let drops = Cell::new(0);
+	let l = &mut List::new();
+	assert!(l.head_handle().is_none());
+	assert!(l.tail_handle().is_none());
+
+	let h1 = l.push_tail(DropCounter(&drops));
+	let h2 = l.push_tail(DropCounter(&drops));
+	l.extend(Some(DropCounter(&drops)));
+
+	// removing through the view while the push handle lives
+	drop(l.head_handle().unwrap().unlink());
+	assert_eq!(1, drops.get());
+	assert!(h1.try_unlink().is_err());
+	assert_eq!(1, drops.get());
+
+	// the list owned link is freed by the list
+	drop(l.tail_handle().unwrap().unlink());
+	assert_eq!(2, drops.get());
+
+	drop(l.tail_handle().unwrap().unlink());
+	assert_eq!(3, drops.get());
+	drop(h2);
+	assert_eq!(3, drops.get());
+	assert!(l.is_empty());
+
+	// the other way round, the push handle goes first
+	let h = l.push_tail(DropCounter(&drops));
+	let v = l.tail_handle().unwrap();
+	drop(h);
+	assert_eq!(3, drops.get());
+	drop(v.unlink());
+	assert_eq!(4, drops.get());
+
+	let h = l.push_tail(DropCounter(&drops));
+	let v = l.head_handle().unwrap();
+	drop(h.unlink());
+	assert_eq!(5, drops.get());
+	drop(v);
+	assert!(l.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn iter_rev_test()
+{
+	let mut l: List<_> = (1..6).collect();
+	let mut fwd: Vec<_> = l.iter().collect();
+	fwd.reverse();
+	assert_eq!(fwd, l.iter_rev().collect::<Vec<_>>());
+	assert_eq!(5, l.iter_rev().len());
+
+	for (i, v) in l.iter_rev_mut().enumerate() {
+		*v = i;
+	}
+	assert_eq!(vec![&4, &3, &2, &1, &0], l.iter().collect::<Vec<_>>());
+
+	let one = list![7];
+	assert_eq!(vec![&7], one.iter_rev().collect::<Vec<_>>());
+	let mut e = List::<i32>::new();
+	assert_eq!(None, e.iter_rev().next());
+	assert_eq!(None, e.iter_rev_mut().next());
+}
+
+#[cfg(test)]
+#[test]
+fn count_if_position_test()
+{
+	let l = list![1, 2, 3, 4];
+	assert_eq!(2, l.count_if(|v| v % 2 == 0));
+	assert_eq!(0, l.count_if(|v| *v > 4));
+	assert_eq!(Some(2), l.position(|v| *v > 2));
+	assert_eq!(Some(0), l.position(|_| true));
+	assert_eq!(None, l.position(|v| *v > 4));
+
+	let e = List::<i32>::new();
+	assert_eq!(0, e.count_if(|_| true));
+	assert_eq!(None, e.position(|_| true));
+}
+
+#[cfg(test)]
+#[test]
+fn remove_first_test()
+{
+	let mut l = list![1, 2, 3, 2];
+	assert_eq!(Some(2), l.remove_first(&2));
+	assert_eq!(vec![&1, &3, &2], l.iter().collect::<Vec<_>>());
+	assert_eq!(Some(1), l.remove_first(&1));
+	assert_eq!(vec![&3, &2], l.iter().collect::<Vec<_>>());
+	assert_eq!(None, l.remove_first(&5));
+	assert_eq!(2, l.len());
+	assert_eq!(None, List::new().remove_first(&1));
+
+	let mut l = list![vec![1], vec![2]];
+	let _h = l.push_tail(vec![3]);
+	assert_eq!(Some(vec![3]), l.remove_first(&vec![3]));
+	assert_eq!(Some(vec![1]), l.remove_first(&vec![1]));
+	assert_eq!(1, l.len());
+}
+
+#[cfg(test)]
+#[test]
+fn split_off_test()
+{
+	let mut l = list![1, 2, 3, 4, 5];
+	let r = l.split_off(2);
+	assert_eq!(list![1, 2], l);
+	assert_eq!(list![3, 4, 5], r);
+
+	let mut l = list![1, 2, 3];
+	let r = l.split_off(0);
+	assert!(l.is_empty());
+	assert_eq!(list![1, 2, 3], r);
+
+	let mut l = list![1, 2, 3];
+	let r = l.split_off(3);
+	assert_eq!(list![1, 2, 3], l);
+	assert!(r.is_empty());
+
+	let mut e = List::<i32>::new();
+	assert!(e.split_off(0).is_empty());
+}
+
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "split_off index (is 4) should be <= len (is 3)")]
+fn split_off_out_of_range_test()
+{
+	list![1, 2, 3].split_off(4);
+}
+
+#[cfg(test)]
+#[test]
+fn dedup_test()
+{
+	let mut l = list![1, 1, 2, 3, 3, 3];
+	l.dedup();
+	assert_eq!(list![1, 2, 3], l);
+	assert_eq!(3, l.len());
+
+	let mut l = list![5, 5, 5, 5];
+	l.dedup();
+	assert_eq!(list![5], l);
+
+	let mut l = list![1];
+	l.dedup();
+	assert_eq!(list![1], l);
+
+	let mut e = List::<i32>::new();
+	e.dedup();
+	assert!(e.is_empty());
+
+	let mut l = list![1, 2, 4, 5, 7, 8];
+	l.dedup_by(|a, b| *a - *b == 1);
+	assert_eq!(list![1, 4, 7], l);
+}
+
+#[cfg(test)]
+#[test]
+fn drop_once_test()
+{
+	let drops = Cell::new(0);
+	{
+		let l = &mut List::new();
+		let h1 = l.push_tail(DropCounter(&drops));
+		let h2 = l.push_head(DropCounter(&drops));
+		let h3 = l.push_tail(DropCounter(&drops));
+		l.extend((0..3).map(|_| DropCounter(&drops)));
+
+		drop(h2);
+		assert_eq!(1, drops.get());
+		drop(l.pop_tail());
+		assert_eq!(2, drops.get());
+		drop(h1.unlink());
+		assert_eq!(3, drops.get());
+
+		// popped while the handle lives, the handle only frees the link
+		drop(l.pop_tail());
+		drop(l.pop_tail());
+		assert_eq!(5, drops.get());
+		drop(l.pop_tail());
+		assert_eq!(6, drops.get());
+		drop(h3);
+		assert_eq!(6, drops.get());
+
+		let h4 = l.push_tail(DropCounter(&drops));
+		l.clear();
+		assert_eq!(7, drops.get());
+		drop(h4);
+		assert_eq!(7, drops.get());
+
+		let _h5 = l.push_tail(DropCounter(&drops));
+		l.extend(Some(DropCounter(&drops)));
+	}
+	// the list went first, then the handle
+	assert_eq!(9, drops.get());
+
+	let drops = Cell::new(0);
+	{
+		let h;
+		let mut l = List::new();
+		h = l.push_tail(DropCounter(&drops));
+		l.extend(Some(DropCounter(&drops)));
+		let _ = l.into_iter().next();
+		assert_eq!(2, drops.get());
+		assert!(h.try_unlink().is_err());
+	}
+	assert_eq!(2, drops.get());
+}
+
+#[cfg(test)]
+#[test]
+fn detach_test()
+{
+	let drops = Cell::new(0);
+	{
+		let l = &mut List::new();
+		{
+			let _h1 = l.push_tail(DropCounter(&drops));
+			l.push_tail(DropCounter(&drops)).detach();
+			l.push_head(DropCounter(&drops)).detach();
+		}
+		assert_eq!(1, drops.get());
+		assert_eq!(2, l.len());
+
+		// a detached element is the list's to remove
+		drop(l.pop_head());
+		assert_eq!(2, drops.get());
+		assert_eq!(1, l.len());
+
+		// detaching a handle whose element is already gone frees its link
+		let h = l.push_tail(DropCounter(&drops));
+		drop(l.pop_tail());
+		h.detach();
+		assert_eq!(3, drops.get());
+		assert_eq!(1, l.len());
+	}
+	assert_eq!(4, drops.get());
+}
+
+#[cfg(test)]
+#[test]
+fn for_each_test()
+{
+	let mut l: List<i32> = (1..4).collect();
+	l.for_each(|e| *e *= 2);
+	assert_eq!(vec![&2, &4, &6], l.iter().collect::<Vec<_>>());
+
+	let mut empty: List<i32> = List::new();
+	empty.for_each(|_| panic!("called on an empty list"));
+}
+
+#[cfg(test)]
+#[test]
+fn map_in_place_test()
+{
+	let mut l: List<String> = vec!["a", "abc", ""].into_iter().map(String::from).collect();
+	l.map_in_place(|s| s.len().to_string());
+	assert_eq!(vec!["1", "3", "0"], l.iter().collect::<Vec<_>>());
+
+	let mut empty: List<String> = List::new();
+	empty.map_in_place(|_| panic!("called on an empty list"));
+	assert!(empty.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn front_back_test()
+{
+	let mut l: List<i32> = (1..4).collect();
+	assert!(core::ptr::eq(l.front().unwrap(), l.peek_head().unwrap()));
+	assert!(core::ptr::eq(l.back().unwrap(), l.peek_tail().unwrap()));
+
+	*l.front_mut().unwrap() = 10;
+	*l.back_mut().unwrap() = 30;
+	assert_eq!(vec![&10, &2, &30], l.iter().collect::<Vec<_>>());
+
+	let empty: List<i32> = List::new();
+	assert_eq!(None, empty.front());
+	assert_eq!(None, empty.back());
+}
+
+#[cfg(test)]
+#[test]
+fn push_front_back_test()
+{
+	let l = &mut List::new();
+	let h2 = l.push_back(2);
+	let h1 = l.push_front(1);
+	let h3 = l.push_back(3);
+	assert_eq!(Some(&1), l.peek_head());
+	assert_eq!(Some(&3), l.peek_tail());
+	assert_eq!(vec![&1, &2, &3], l.iter().collect::<Vec<_>>());
+
+	assert_eq!(1, h1.unlink());
+	assert_eq!(Some(&2), l.peek_head());
+	h2.detach();
+	h3.detach();
+	assert_eq!(2, l.len());
+}
+
+#[cfg(test)]
+#[test]
+fn concat_test()
+{
+	let l = List::concat(vec![list![1, 2], List::new(), list![3]]);
+	assert_eq!(3, l.len());
+	assert_eq!(vec![&1, &2, &3], l.iter().collect::<Vec<_>>());
+
+	let empty: List<i32> = List::concat(vec![]);
+	assert!(empty.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn to_vec_test()
+{
+	let l = list![1, 2, 3];
+	let v = l.to_vec();
+	assert_eq!(vec![1, 2, 3], v);
+	assert_eq!(3, v.capacity());
+	assert_eq!(3, l.len());
+
+	let empty: List<i32> = List::new();
+	assert!(empty.to_vec().is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn from_slice_test()
+{
+	let l = List::from_slice(&[1, 2, 3]);
+	assert_eq!(3, l.len());
+	assert_eq!(vec![1, 2, 3], l.to_vec());
+
+	let empty: List<i32> = List::from_slice(&[]);
+	assert!(empty.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn merge_sorted_test()
+{
+	let mut a = list![1, 3, 5];
+	let mut b = list![2, 4, 6];
+	a.merge_sorted(&mut b);
+	assert_eq!(vec![1, 2, 3, 4, 5, 6], a.to_vec());
+	assert_eq!(6, a.len());
+	assert!(b.is_empty());
+	assert_eq!(None, b.peek_head());
+
+	let mut empty = List::new();
+	empty.merge_sorted(&mut a);
+	assert_eq!(vec![1, 2, 3, 4, 5, 6], empty.to_vec());
+	empty.merge_sorted(&mut List::new());
+	assert_eq!(6, empty.len());
+
+	// equal elements from self come first
+	let mut a = list![(1, 'a'), (2, 'a')];
+	let mut b = list![(1, 'b'), (2, 'b'), (3, 'b')];
+	let mut c = List::new();
+	let h = c.push_tail((2, 'c'));
+	a.merge_sorted(&mut b);
+	let mut c2 = list![(0, 'c')];
+	c2.append(&mut c);
+	a.merge_sorted(&mut c2);
+	assert_eq!(vec![(0, 'c'), (1, 'a'), (1, 'b'), (2, 'a'), (2, 'b'), (2, 'c'), (3, 'b')], a.to_vec());
+	assert_eq!((2, 'c'), h.unlink());
+	assert_eq!(6, a.len());
+}
+
+#[cfg(test)]
+#[test]
+fn sort_test()
+{
+	let l = &mut List::new();
+	let h3 = l.push_tail(3);
+	let h1 = l.push_tail(1);
+	let h2 = l.push_tail(2);
+	l.sort();
+	assert_eq!(vec![1, 2, 3], l.to_vec());
+	assert_eq!(&1, h1.as_ref());
+	assert_eq!(&2, h2.as_ref());
+	assert_eq!(&3, h3.as_ref());
+
+	assert_eq!(2, h2.unlink());
+	assert_eq!(vec![1, 3], l.to_vec());
+	h1.detach();
+	h3.detach();
+
+	l.sort_by(|a, b| b.cmp(a));
+	assert_eq!(vec![3, 1], l.to_vec());
+	assert_eq!(2, l.len());
+
+	let mut empty: List<i32> = List::new();
+	empty.sort();
+	assert!(empty.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn sort_by_stable_test()
+{
+	let mut l: List<(i32, usize)> = [5, 3, 9, 3, 1, 5, 0, 7, 3, 2, 8]
+		.iter().enumerate().map(|(i, &k)| (k, i)).collect();
+	let mut v = l.to_vec();
+	l.sort_by(|a, b| a.0.cmp(&b.0));
+	v.sort_by_key(|a| a.0);
+	assert_eq!(v, l.to_vec());
+	assert_eq!(v.iter().rev().collect::<Vec<_>>(), l.iter().rev().collect::<Vec<_>>());
+}
+
+#[cfg(test)]
+#[test]
+fn take_head_tail_test()
+{
+	let drops = Cell::new(0);
+	let mut l: List<_> = (0..3).map(|i| (i, DropCounter(&drops))).collect();
+	assert_eq!(Some((0, false)), l.take_head().map(|(v, empty)| (v.0, empty)));
+	assert_eq!(Some((2, false)), l.take_tail().map(|(v, empty)| (v.0, empty)));
+	assert_eq!(Some((1, true)), l.take_head().map(|(v, empty)| (v.0, empty)));
+	assert!(l.take_head().is_none());
+	assert!(l.take_tail().is_none());
+	assert_eq!(3, drops.get());
+}
+
+#[cfg(test)]
+#[test]
+fn fused_iter_test()
+{
+	fn fused<I: FusedIterator>(mut i: I) -> usize
+	{
+		let n = i.by_ref().count();
+		for _ in 0..3 {
+			assert!(i.next().is_none());
+		}
+		n
+	}
+
+	let mut l = list![1, 2, 3];
+	assert_eq!(3, fused(l.iter()));
+	assert_eq!(3, fused(l.iter_mut()));
+	assert_eq!(3, fused(l.iter_rev()));
+	assert_eq!(3, fused(l.iter_rev_mut()));
+	assert_eq!(1, fused(l.extract_if(|v| *v == 2)));
+	assert_eq!(2, fused(l.clone().into_iter()));
+
+	let mut i = l.iter();
+	assert_eq!(Some(&1), i.next());
+	assert_eq!(Some(&3), i.next_back());
+	assert!(i.next().is_none());
+	assert!(i.next_back().is_none());
+	assert!(i.next().is_none());
+}
+
+#[cfg(test)]
+#[test]
+fn pool_test()
+{
+	let drops = Cell::new(0);
+	let mut l = List::new();
+	l.reserve(4);
+	assert_eq!(0, l.len());
+	assert_eq!(4, l.capacity());
+
+	l.extend((0..4).map(|i| (i, DropCounter(&drops))));
+	assert_eq!(4, l.capacity());
+	l.extend(Some((4, DropCounter(&drops))));
+	assert_eq!(5, l.capacity());
+
+	// pooled links don't hold on to the values
+	l.pop_head();
+	l.pop_tail();
+	assert_eq!(2, drops.get());
+	assert_eq!(5, l.capacity());
+
+	// clear frees the links instead of pooling them
+	l.clear();
+	assert_eq!(5, drops.get());
+	assert_eq!(2, l.capacity());
+
+	let h = l.push_tail((10, DropCounter(&drops)));
+	l.extend(Some((11, DropCounter(&drops))));
+	assert_eq!(vec![10, 11], l.iter().map(|v| v.0).collect::<Vec<_>>());
+	assert_eq!(2, l.capacity());
+
+	// a handle frees its link itself
+	drop(h);
+	assert_eq!(6, drops.get());
+	assert_eq!(1, l.capacity());
+
+	l.pop_head();
+	assert_eq!(1, l.capacity());
+	l.shrink_to_fit();
+	assert_eq!(0, l.capacity());
+	l.reserve(1);
+	assert_eq!(1, l.capacity());
+
+	// the pool never grows past what reserve asked for
+	l.extend((0..6).map(|i| (i, DropCounter(&drops))));
+	l.truncate(0);
+	assert_eq!(1, l.capacity());
+
+	// without reserve nothing is pooled
+	let mut l: List<_> = (0..3).collect();
+	l.pop_head();
+	l.pop_tail();
+	assert_eq!(1, l.capacity());
+}
+
+#[cfg(test)]
+#[test]
+fn pool_reuse_test()
+{
+	let mut l = List::new();
+	l.reserve(1);
+	for i in 0..10_000 {
+		l.extend(Some(i));
+		assert_eq!(Some(i), l.pop_head());
+	}
+	assert_eq!(1, l.capacity());
+	assert!(l.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn split_at_handle_test()
+{
+	let mut l = List::new();
+	let h1 = l.push_tail(1);
+	l.extend(2..4);
+	let (a, mut b) = l.split_at_handle(&h1);
+	assert!(a.is_empty());
+	assert_eq!(vec![1, 2, 3], b.to_vec());
+
+	let h2 = b.insert_after(&h1, 5);
+	let (a, b) = b.split_at_handle(&h2);
+	assert_eq!(vec![1], a.to_vec());
+	assert_eq!(vec![5, 2, 3], b.to_vec());
+	assert_eq!((1, 3), (a.len(), b.len()));
+
+	let mut l = b;
+	let h3 = l.push_tail(4);
+	let (mut a, b) = l.split_at_handle(&h3);
+	assert_eq!(vec![5, 2, 3], a.to_vec());
+	assert_eq!(vec![4], b.to_vec());
+
+	// the handles follow their elements
+	assert_eq!(4, h3.unlink());
+	assert!(b.is_empty());
+	assert_eq!(5, h2.unlink());
+	a.clear();
+	assert_eq!(1, h1.unlink());
+}
+
+#[cfg(test)]
+#[test]
+fn iter_nth_last_test()
+{
+	let l = list![1, 2, 3, 4];
+	assert_eq!(l.peek_tail(), l.iter().last());
+	assert_eq!(Some(&3), l.iter().nth(2));
+
+	let mut i = l.iter();
+	assert_eq!(Some(&2), i.nth(1));
+	assert_eq!(2, i.len());
+	assert_eq!(Some(&4), i.nth(1));
+	assert_eq!(None, i.nth(1));
+
+	let mut i = l.iter();
+	assert_eq!(Some(&4), i.next_back());
+	assert_eq!(None, i.nth(3));
+	assert_eq!(None, i.next());
+	assert_eq!(None, i.last());
+
+	let mut i = l.iter();
+	i.next_back();
+	assert_eq!(Some(&3), i.last());
+
+	let empty: List<i32> = List::new();
+	assert_eq!(None, empty.iter().last());
+	assert_eq!(None, empty.iter().nth(1));
+}
+
+#[cfg(test)]
+#[test]
+fn replace_head_tail_test()
+{
+	let l = &mut List::new();
+	let h1 = l.push_tail(1);
+	l.extend(2..4);
+	assert_eq!(Some(1), l.replace_head(9));
+	assert_eq!(Some(3), l.replace_tail(7));
+	assert_eq!(vec![9, 2, 7], l.to_vec());
+	assert_eq!(3, l.len());
+	assert_eq!(9, h1.unlink());
+
+	let mut empty = List::new();
+	assert_eq!(None, empty.replace_head(1));
+	assert_eq!(None, empty.replace_tail(1));
+	assert!(empty.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn iter_handles_test()
+{
+	let drops = Cell::new(0);
+	let mut l: List<_> = (1..8).map(|i| (i, DropCounter(&drops))).collect();
+	let h = l.push_tail((9, DropCounter(&drops)));
+	for mut g in l.iter_handles() {
+		if g.get().0 % 2 == 1 {
+			g.remove();
+		} else {
+			g.get_mut().0 *= 10;
+		}
+	}
+	assert_eq!(5, drops.get());
+	assert_eq!(vec![20, 40, 60], l.iter().map(|v| v.0).collect::<Vec<_>>());
+	assert_eq!(3, l.len());
+	assert!(h.try_unlink().is_err());
+
+	// guards can outlive the iterator
+	let guards: Vec<_> = l.iter_handles().collect();
+	for g in guards.into_iter().rev() {
+		g.remove();
+	}
+	assert!(l.is_empty());
+	assert_eq!(8, drops.get());
+	assert_eq!(0, l.iter_handles().count());
+
+	// the push handles go away before the guards
+	let h1 = l.push_tail((1, DropCounter(&drops)));
+	let h2 = l.push_tail((2, DropCounter(&drops)));
+	let h3 = l.push_tail((3, DropCounter(&drops)));
+	let guards: Vec<_> = l.iter_handles().collect();
+	drop(h1);
+	assert_eq!(8, drops.get());
+	assert_eq!(2, h2.unlink().0);
+	assert_eq!(9, drops.get());
+	let mut guards = guards.into_iter();
+	assert_eq!(1, guards.next().unwrap().remove().0);
+	assert_eq!(10, drops.get());
+	drop(guards);
+	assert_eq!(vec![3], l.iter().map(|v| v.0).collect::<Vec<_>>());
+	drop(h3);
+	assert!(l.is_empty());
+	assert_eq!(11, drops.get());
+}
+
+#[cfg(test)]
+#[test]
+fn entry_head_test()
+{
+	let mut l = List::new();
+	match l.entry_head() {
+		HeadEntry::Vacant(_) => {}
+		HeadEntry::Occupied(_) => panic!("empty list has a head"),
+	}
+	*l.entry_head().or_insert(1) += 10;
+	assert_eq!(vec![11], l.to_vec());
+	assert_eq!(1, l.len());
+
+	// an occupied head is left alone
+	*l.entry_head().or_insert_with(|| panic!("head is there")) += 1;
+	assert_eq!(12, *l.entry_head().or_insert(5));
+	assert_eq!(vec![12], l.to_vec());
+
+	l.extend(Some(3));
+	match l.entry_head() {
+		HeadEntry::Occupied(v) => *v = 0,
+		HeadEntry::Vacant(_) => panic!("list has a head"),
+	}
+	assert_eq!(vec![0, 3], l.to_vec());
+}
+
+#[cfg(test)]
+#[test]
+fn splice_after_test()
+{
+	let mut l = list![1];
+	let h2 = l.push_tail(2);
+	l.extend(Some(3));
+	let mut other = List::new();
+	let h9 = other.push_tail(9);
+	other.extend(Some(9));
+
+	l.splice_after(&h2, &mut other);
+	assert_eq!(vec![1, 2, 9, 9, 3], l.to_vec());
+	assert_eq!(5, l.len());
+	assert!(other.is_empty());
+
+	l.splice_after(&h2, &mut List::new());
+	assert_eq!(5, l.len());
+
+	// moved handles count against their new list
+	assert_eq!(9, h9.unlink());
+	assert_eq!(4, l.len());
+	other.extend(Some(4));
+	assert_eq!(1, other.len());
+	drop(h2);
+	assert_eq!(vec![1, 9, 3], l.to_vec());
+}
+
+#[cfg(test)]
+#[test]
+fn is_linked_test()
+{
+	let h3;
+	{
+		let mut l = List::new();
+		let h1 = l.push_tail(1);
+		let h2 = l.push_tail(2);
+		assert!(h1.is_linked() && h2.is_linked());
+
+		l.pop_head();
+		assert!(!h1.is_linked());
+		assert!(h2.is_linked());
+
+		let mut other = List::new();
+		other.append(&mut l);
+		assert!(h2.is_linked());
+		other.clear();
+		assert!(!h2.is_linked());
+
+		h3 = l.push_tail(3);
+		assert!(h3.is_linked());
+	}
+	// the list is gone
+	assert!(!h3.is_linked());
+	assert!(h3.try_unlink().is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn search_test()
+{
+	let l = list![1, 3, 5];
+	assert_eq!(Ok(1), l.search(|v| v.cmp(&3)));
+	assert_eq!(Err(2), l.search(|v| v.cmp(&4)));
+	assert_eq!(Err(0), l.search(|v| v.cmp(&0)));
+	assert_eq!(Err(3), l.search(|v| v.cmp(&6)));
+
+	let empty: List<i32> = List::new();
+	assert_eq!(Err(0), empty.search(|v| v.cmp(&1)));
+}
+
+#[cfg(test)]
+#[test]
+fn chunks_test()
+{
+	let l = list![1, 2, 3, 4, 5];
+	let c = l.chunks(2);
+	assert_eq!(3, c.len());
+	assert_eq!(vec![vec![&1, &2], vec![&3, &4], vec![&5]], c.collect::<Vec<_>>());
+	assert_eq!(vec![vec![&1, &2, &3, &4, &5]], l.chunks(5).collect::<Vec<_>>());
+	assert_eq!(5, l.chunks(1).count());
+
+	let empty: List<i32> = List::new();
+	assert_eq!(0, empty.chunks(3).count());
+}
+
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "chunk size must be non-zero")]
+fn chunks_zero_test()
+{
+	let l = list![1];
+	l.chunks(0);
+}
+
+#[cfg(test)]
+#[test]
+fn windows_test()
+{
+	let l = list![1, 2, 3];
+	let w = l.windows(2);
+	assert_eq!(2, w.len());
+	assert_eq!(vec![vec![&1, &2], vec![&2, &3]], w.collect::<Vec<_>>());
+	assert_eq!(vec![vec![&1, &2, &3]], l.windows(3).collect::<Vec<_>>());
+	assert_eq!(3, l.windows(1).count());
+	assert_eq!(0, l.windows(4).len());
+	assert_eq!(0, l.windows(4).count());
+
+	let mut w = l.windows(2);
+	w.next();
+	assert_eq!(1, w.len());
+	w.next();
+	assert_eq!(None, w.next());
+	assert_eq!(None, w.next());
+}
+
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "window size must be non-zero")]
+fn windows_zero_test()
+{
+	let l = list![1];
+	l.windows(0);
+}
+
+#[cfg(test)]
+#[test]
+fn partition_test()
+{
+	let mut l = list![1, 2];
+	let h3 = l.push_tail(3);
+	l.extend(Some(4));
+	let (even, odd) = l.partition(|v| v % 2 == 0);
+	assert_eq!(vec![2, 4], even.to_vec());
+	assert_eq!(vec![1, 3], odd.to_vec());
+	assert_eq!((2, 2), (even.len(), odd.len()));
+
+	// the handle moved along with its element
+	assert_eq!(3, h3.unlink());
+	assert_eq!(1, odd.len());
+	assert_eq!(2, even.len());
+
+	let (a, b) = List::<i32>::new().partition(|_| true);
+	assert!(a.is_empty() && b.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn zip_map_test()
+{
+	let a = list![1, 2, 3];
+	let b = list![10, 20];
+	assert_eq!(vec![11, 22], a.zip_map(&b, |x, y| x + y).to_vec());
+	assert_eq!(vec![11, 22], b.zip_map(&a, |x, y| x + y).to_vec());
+	assert_eq!(vec![1, 2, 3], a.to_vec());
+
+	let names = list!["a", "b"];
+	assert_eq!(vec!["a1".to_string(), "b2".to_string()],
+		names.zip_map(&a, |n, i| format!("{}{}", n, i)).to_vec());
+	assert!(a.zip_map(&List::<i32>::new(), |x, y| x + y).is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn heap_bytes_test()
+{
+	let link = mem::size_of::<Link<u64>>();
+	let mut l: List<u64> = List::new();
+	assert_eq!(link, l.heap_bytes());
+	l.extend(vec![1, 2, 3]);
+	assert_eq!(4 * link, l.heap_bytes());
+
+	l.pop_tail();
+	assert_eq!(3 * link, l.heap_bytes());
+	l.reserve(2);
+	assert_eq!(5 * link, l.heap_bytes());
+	l.shrink_to_fit();
+	assert_eq!(3 * link, l.heap_bytes());
+}
+
+#[cfg(test)]
+#[test]
+fn unlink_with_neighbors_test()
+{
+	let l = &mut List::new();
+	let h1 = l.push_tail(1);
+	let h2 = l.push_tail(2);
+	let h3 = l.push_tail(3);
+	let h4 = l.push_tail(4);
+
+	assert_eq!((2, Some(&1), Some(&3)), l.unlink_with_neighbors(h2));
+	assert_eq!((1, None, Some(&3)), l.unlink_with_neighbors(h1));
+	assert_eq!((4, Some(&3), None), l.unlink_with_neighbors(h4));
+	assert_eq!((3, None, None), l.unlink_with_neighbors(h3));
+	assert!(l.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn rotate_to_handle_test()
+{
+	let mut l = list![1, 2];
+	let h3 = l.push_tail(3);
+	l.extend(Some(4));
+	l.rotate_to_handle(&h3);
+	assert_eq!(vec![3, 4, 1, 2], l.to_vec());
+	assert_eq!(vec![2, 1, 4, 3], l.iter_rev().cloned().collect::<Vec<_>>());
+	l.rotate_to_handle(&h3);
+	assert_eq!(vec![3, 4, 1, 2], l.to_vec());
+	assert_eq!(4, l.len());
+
+	let h5 = l.push_tail(5);
+	l.rotate_to_handle(&h5);
+	assert_eq!(vec![5, 3, 4, 1, 2], l.to_vec());
+	assert_eq!(Some(&2), l.peek_tail());
+	h3.detach();
+	h5.detach();
+}
+
+#[cfg(test)]
+#[test]
+fn push_tail_bounded_test()
+{
+	let l = &mut List::new();
+	let h1 = l.push_tail_bounded(1, 2).ok().unwrap();
+	let h2 = l.push_tail_bounded(2, 2).ok().unwrap();
+	assert_eq!(Some(3), l.push_tail_bounded(3, 2).err());
+	assert_eq!(vec![1, 2], l.to_vec());
+
+	assert_eq!(1, h1.unlink());
+	// the handle drops right away and takes 3 along
+	assert!(l.push_tail_bounded(3, 2).is_ok());
+	assert_eq!(vec![2], l.to_vec());
+	assert_eq!(Some(4), l.push_tail_bounded(4, 0).err());
+	h2.detach();
+}
+
+#[cfg(test)]
+#[test]
+fn push_tail_evicting_test()
+{
+	let mut l = List::new();
+	assert_eq!(None, l.push_tail_evicting(1, 3));
+	assert_eq!(None, l.push_tail_evicting(2, 3));
+	assert_eq!(None, l.push_tail_evicting(3, 3));
+	assert_eq!(Some(1), l.push_tail_evicting(4, 3));
+	assert_eq!(vec![2, 3, 4], l.to_vec());
+	assert_eq!(Some(2), l.push_tail_evicting(5, 3));
+	assert_eq!(vec![3, 4, 5], l.to_vec());
+
+	assert_eq!(3, l.len());
+
+	let mut e = List::new();
+	assert_eq!(Some(6), e.push_tail_evicting(6, 0));
+	assert!(e.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "push_tail_evicting cap (is 2) should be >= len (is 3)")]
+fn push_tail_evicting_over_cap_test()
+{
+	let mut l = list![1, 2, 3];
+	l.push_tail_evicting(4, 2);
+}
+
+#[cfg(test)]
+#[test]
+fn truncate_test()
+{
+	let drops = Cell::new(0);
+	let mut l: List<_> = (1..6).map(|i| (i, DropCounter(&drops))).collect();
+	l.truncate(7);
+	l.truncate(5);
+	assert_eq!(5, l.len());
+	assert_eq!(0, drops.get());
+
+	l.truncate(2);
+	assert_eq!(vec![1, 2], l.iter().map(|v| v.0).collect::<Vec<_>>());
+	assert_eq!(3, drops.get());
+	assert_eq!(Some(2), l.peek_tail().map(|v| v.0));
+
+	l.truncate(0);
+	assert!(l.is_empty());
+	assert_eq!(5, drops.get());
+}
 
 #[cfg(test)]
 #[test]
-fn link_unlink()
+fn get_back_test()
 {
-	let mut s = Handle::new_sentinel();
-	let mut h1 = Handle::new(1);
-	let mut h2 = Handle::new(2);
-	let mut h3 = Handle::new(3);
-	
-	insert_after(&mut s,  &mut h1);
-	insert_after(&mut h1, &mut h2);
-	insert_after(&mut h2, &mut h3);
+	let l = list![1, 2, 3];
+	assert_eq!(Some(&3), l.get_back(0));
+	assert_eq!(Some(&2), l.get_back(1));
+	assert_eq!(Some(&1), l.get_back(2));
+	assert_eq!(None, l.get_back(3));
 
-	assert_eq!(unsafe {&*s.next}, &*h1);
-	assert_eq!(unsafe {&*h1.prev}, &*s);
+	let empty: List<i32> = List::new();
+	assert_eq!(None, empty.get_back(0));
+}
 
-	assert_eq!(unsafe {&*h1.next}, &*h2);
-	assert_eq!(unsafe {&*h2.prev}, &*h1);
-	
-	assert_eq!(unsafe {&*h2.next}, &*h3);
-	assert_eq!(unsafe {&*h3.prev}, &*h2);
-	
-	assert_eq!(unsafe {&*h3.next}, &*s);
-	assert_eq!(unsafe {&*s.prev}, &*h3);
+#[cfg(test)]
+#[test]
+fn sum_test()
+{
+	let l = list![1, 2, 3];
+	assert_eq!(6, l.sum());
+	assert_eq!(6, l.iter().copied().sum::<i32>());
+	assert_eq!(6, l.iter().product::<i32>());
+	assert_eq!(0, List::<i32>::new().sum());
+	assert_eq!(2.5, list![1.0, 1.5].sum());
+}
 
-	h2.into_inner();
+#[cfg(test)]
+#[test]
+fn retain_mut_test()
+{
+	let drops = Cell::new(0);
+	let mut l: List<_> = [2, 1, 3, 1].iter().map(|&ttl| (ttl, DropCounter(&drops))).collect();
+	let h = l.push_tail((1, DropCounter(&drops)));
+	let tick = |l: &mut List<(i32, DropCounter)>| l.retain_mut(|v| {
+		v.0 -= 1;
+		v.0 > 0
+	});
 
-	assert_eq!(unsafe {&*s.next}, &*h1);
-	assert_eq!(unsafe {&*h1.prev}, &*s);
-	
-	assert_eq!(unsafe {&*h1.next}, &*h3);
-	assert_eq!(unsafe {&*h3.prev}, &*h1);
-	
-	assert_eq!(unsafe {&*h3.next}, &*s);
-	assert_eq!(unsafe {&*s.prev}, &*h3);
-	
-	let mut h2 = Handle::new(2);
-	insert_after(&mut h1, &mut h2);
-	
-	h1.into_inner();
-	assert_eq!(unsafe {&*s.next}, &*h2);
-	h2.into_inner();
-	assert_eq!(unsafe {&*s.next}, &*h3);
-	assert_eq!(unsafe {&*s.prev}, &*h3);
-	assert_eq!(unsafe {&*h3.prev}, &*s);
-	assert_eq!(unsafe {&*h3.next}, &*s);
-	h3.into_inner();
-	assert_eq!(unsafe {&*s.prev}, &*s);
-	assert_eq!(unsafe {&*s.next}, &*s);
-	
-	s.into_inner();
+	tick(&mut l);
+	assert_eq!(vec![1, 2], l.iter().map(|v| v.0).collect::<Vec<_>>());
+	assert_eq!(3, drops.get());
+	assert!(!h.is_linked());
+	tick(&mut l);
+	assert_eq!(vec![1], l.iter().map(|v| v.0).collect::<Vec<_>>());
+	tick(&mut l);
+	assert!(l.is_empty());
+	assert_eq!(5, drops.get());
 }
 
 #[cfg(test)]
 #[test]
-fn iter_test()
+fn into_vec_test()
+{
+	let drops = Cell::new(0);
+	let mut l: List<_> = (1..4).map(|i| (i, DropCounter(&drops))).collect();
+	let h = l.push_tail((4, DropCounter(&drops)));
+	let v = l.into_vec();
+	assert_eq!(vec![1, 2, 3, 4], v.iter().map(|e| e.0).collect::<Vec<_>>());
+	assert_eq!(0, drops.get());
+	assert!(!h.is_linked());
+	drop(h);
+	drop(v);
+	assert_eq!(4, drops.get());
+}
+
+#[cfg(test)]
+#[test]
+fn non_null_test()
 {
+	assert_eq!(mem::size_of::<Handle<i32>>(), mem::size_of::<Option<Handle<i32>>>());
+	// the unlinked marker costs nothing over a raw pointer
+	assert_eq!(mem::size_of::<*mut Link<i32>>(), mem::size_of::<Option<NonNull<Link<i32>>>>());
+
 	let l = &mut List::new();
-	let h1 = l.push_head(1);
-    {
+	let h1 = l.push_tail(1);
 	let h2 = l.push_tail(2);
-    }
-    let h3 = l.push_tail(3);
+	for link in [sealed::HasLink::link(&h1), sealed::HasLink::link(&h2)] {
+		assert!(unsafe { (*link).next.is_some() && (*link).prev.is_some() });
+	}
+	let link = sealed::HasLink::link(&h1);
+	let mut l2 = l.split_after(&h1);
+	assert_eq!(link, l.sentinel.next());
+	l.pop_head();
+	assert!(unsafe { (*link).next.is_none() && (*link).prev.is_none() });
+	assert_eq!(2, h2.unlink());
+	assert!(l2.pop_head().is_none());
+	assert!(h1.try_unlink().is_err());
+}
 
-	let mut i = l.iter();
-	assert_eq!(Some(&1), i.next());
-	//assert_eq!(Some(&2), i.next());
-	assert_eq!(Some(&3), i.next());
-	assert_eq!(None, i.next());
+#[cfg(test)]
+#[test]
+fn validate_test()
+{
+	let mut l = List::new();
+	assert!(l.validate());
+	let h = l.push_tail(2);
+	l.extend(vec![3, 4]);
+	l.push_head(1).detach();
+	assert!(l.validate());
+	l.reverse();
+	l.rotate_left(1);
+	assert!(l.validate());
 
-    h1.unlink();
-    //h2.unlink();
-    h3.unlink();
+	// a broken back pointer
+	let link = sealed::HasLink::link(&h);
+	let prev = unsafe { (*link).prev() };
+	unsafe { (*link).prev = NonNull::new(link) };
+	assert!(!l.validate());
+	unsafe { (*link).prev = NonNull::new(prev) };
+	assert!(l.validate());
+
+	// a length that doesn't match the chain
+	l.shared.len.set(5);
+	assert!(!l.validate());
+	l.shared.len.set(3);
+	assert!(!l.validate());
+	l.shared.len.set(4);
+	assert!(l.validate());
 }
 
 #[cfg(test)]
 #[test]
-fn iter_mut_test()
+fn prepend_test()
+{
+	let mut l = list![3, 4];
+	let mut other = List::new();
+	let h1 = other.push_tail(1);
+	other.extend(Some(2));
+	l.prepend(&mut other);
+	assert_eq!(vec![1, 2, 3, 4], l.to_vec());
+	assert_eq!(4, l.len());
+	assert!(other.is_empty());
+
+	l.prepend(&mut List::new());
+	assert_eq!(4, l.len());
+	assert_eq!(1, h1.unlink());
+	assert_eq!(3, l.len());
+	assert!(l.validate());
+}
+
+#[cfg(test)]
+#[test]
+fn swap_values_test()
 {
 	let l = &mut List::new();
-	let h1 = l.push_head(1);
+	let mut h1 = l.push_tail(1);
+	let h2 = l.push_tail(2);
+	let mut h3 = l.push_tail(3);
+	List::swap_values(&mut h1, &mut h3);
+	assert_eq!(vec![3, 2, 1], l.to_vec());
+	assert_eq!(&3, h1.as_ref());
+
+	// swap moves the links, so the handles go along with their values
+	l.swap(&h1, &h2);
+	assert_eq!(vec![2, 3, 1], l.to_vec());
+	assert_eq!(3, h1.unlink());
+	assert_eq!(1, h3.unlink());
+	assert_eq!(2, h2.unlink());
+}
+
+#[cfg(test)]
+#[test]
+fn group_runs_test()
+{
+	let l = list![1, 1, 2, 3, 3];
+	assert_eq!(vec![vec![&1, &1], vec![&2], vec![&3, &3]],
+		l.group_runs(|a, b| a == b).collect::<Vec<_>>());
+	assert_eq!(vec![vec![&1, &1, &2, &3, &3]],
+		l.group_runs(|a, b| b - a <= 1).collect::<Vec<_>>());
+	assert_eq!(5, l.group_runs(|_, _| false).count());
+
+	let single = list![7];
+	assert_eq!(vec![vec![&7]], single.group_runs(|_, _| true).collect::<Vec<_>>());
+	assert_eq!(0, List::<i32>::new().group_runs(|_, _| true).count());
+}
+
+#[cfg(test)]
+#[test]
+fn fold_reduce_test()
+{
+	let l = list![3, 9, 2];
+	assert_eq!(14, l.fold(0, |acc, v| acc + v));
+	assert_eq!("392", l.fold(String::new(), |acc, v| acc + &v.to_string()));
+	assert_eq!(Some(9), l.reduce(|a, b| *a.max(b)));
+	assert_eq!(Some(5), list![5].reduce(|_, _| panic!("one element")));
+
+	let empty: List<i32> = List::new();
+	assert_eq!(1, empty.fold(1, |acc, v| acc * v));
+	assert_eq!(None, empty.reduce(|a, b| a + b));
+}
+
+#[cfg(test)]
+#[test]
+fn min_max_test()
+{
+	let l = list![3, 1, 2];
+	assert_eq!(Some(&1), l.min());
+	assert_eq!(Some(&3), l.max());
+
+	let empty: List<i32> = List::new();
+	assert_eq!(None, empty.min());
+	assert_eq!(None, empty.max());
+}
+
+#[cfg(test)]
+#[test]
+fn insert_sorted_test()
+{
+	let mut l = list![1, 2, 4];
+	let h = l.insert_sorted(3);
+	assert_eq!(vec![1, 2, 3, 4], l.to_vec());
+	assert_eq!(3, h.unlink());
+
+	l.insert_sorted(0).detach();
+	l.insert_sorted(5).detach();
+	assert_eq!(vec![0, 1, 2, 4, 5], l.to_vec());
+
+	// equal elements keep their insertion order
+	#[derive(Debug, Clone, Copy)]
+	struct Keyed(i32, usize);
+	impl PartialEq for Keyed { fn eq(&self, o: &Self) -> bool { self.0 == o.0 } }
+	impl Eq for Keyed {}
+	impl PartialOrd for Keyed { fn partial_cmp(&self, o: &Self) -> Option<Ordering> { Some(self.cmp(o)) } }
+	impl Ord for Keyed { fn cmp(&self, o: &Self) -> Ordering { self.0.cmp(&o.0) } }
+
+	let mut l = List::new();
+	for (i, k) in [2, 1, 2, 1].iter().enumerate() {
+		l.insert_sorted(Keyed(*k, i)).detach();
+	}
+	assert_eq!(vec![1, 3, 0, 2], l.iter().map(|k| k.1).collect::<Vec<_>>());
+
+	let mut empty = List::new();
+	empty.insert_sorted(1).detach();
+	assert_eq!(vec![1], empty.to_vec());
+}
+
+#[cfg(test)]
+#[test]
+fn peek_next_prev_test()
+{
+	let l = &mut List::new();
+	let h1 = l.push_tail(1);
 	let h2 = l.push_tail(2);
 	let h3 = l.push_tail(3);
+	assert_eq!((Some(&1), Some(&3)), (h2.peek_prev(), h2.peek_next()));
+	assert_eq!((Some(&2), None), (h3.peek_prev(), h3.peek_next()));
+	assert_eq!((None, Some(&2)), (h1.peek_prev(), h1.peek_next()));
 
+	drop(h2);
+	assert_eq!(Some(&3), h1.peek_next());
+	l.pop_head();
+	assert_eq!((None, None), (h1.peek_prev(), h1.peek_next()));
+	assert_eq!((None, None), (h3.peek_prev(), h3.peek_next()));
+}
+
+#[cfg(test)]
+#[test]
+fn drain_test()
+{
+	let drops = Cell::new(0);
+	let mut l: List<_> = (0..5).map(|i| (i, DropCounter(&drops))).collect();
+	let h = l.push_tail((5, DropCounter(&drops)));
 	{
-	let mut i = l.iter_mut();
-	assert_eq!(Some(&mut 1), i.next());
-	assert_eq!(Some(&mut 2), i.next());
-	assert_eq!(Some(&mut 3), i.next());
-	assert_eq!(None, i.next());
+		let mut d = l.drain();
+		assert_eq!(6, d.len());
+		assert_eq!(Some(0), d.next().map(|v| v.0));
+		assert_eq!(Some(5), d.next_back().map(|v| v.0));
+		assert_eq!(4, d.len());
 	}
+	assert!(l.is_empty());
+	assert_eq!(6, drops.get());
+	assert!(!h.is_linked());
 
-	l.iter_mut().fold(3, |i, v| {
-		*v = i;
-		i - 1
-	});
+	l.extend((0..3).map(|i| (i, DropCounter(&drops))));
+	assert_eq!(vec![0, 1, 2], l.drain().map(|v| v.0).collect::<Vec<_>>());
+	assert!(l.is_empty());
+	assert_eq!(9, drops.get());
+}
 
+#[cfg(test)]
+#[test]
+fn handles_survive_move_test()
+{
+	struct Holder
 	{
-	let mut i = l.iter_mut();
-	assert_eq!(Some(&mut 3), i.next());
-	assert_eq!(Some(&mut 2), i.next());
-	assert_eq!(Some(&mut 1), i.next());
-	assert_eq!(None, i.next());
+		list: List<i32>,
 	}
-	
-	assert_eq!(&3, h1.as_ref());
+
+	let mut l = List::new();
+	let h1 = l.push_tail(1);
+	let h2 = l.push_tail(2);
+	let moved = l;
+	assert_eq!(&1, h1.as_ref());
+
+	let mut holder = Holder { list: moved };
+	let mut boxed = Box::new(Holder { list: List::new() });
+	mem::swap(&mut holder.list, &mut boxed.list);
 	assert_eq!(&2, h2.as_ref());
-	assert_eq!(&1, h3.as_ref());
+	assert_eq!(Some(&2), h1.peek_next());
+	assert!(holder.list.is_empty());
+
+	assert_eq!(1, h1.unlink());
+	assert_eq!(vec![2], boxed.list.to_vec());
+	let mut v = vec![boxed];
+	v.reserve(100);
+	assert_eq!(2, h2.unlink());
+	assert!(v[0].list.is_empty());
 }
 
 #[cfg(test)]
-//#[test]
-fn test_drop()
+#[test]
+fn index_test()
 {
-    let line = &mut String::new();
-    
-    std::io::stdin().read_line(line);
-	
-    let mut s = Handle::new_sentinel();
-    let mut vec = vec![];
+	let mut l = list![1, 2, 3, 4];
+	assert_eq!(2, l[1]);
+	assert_eq!(4, l[3]);
+	l[2] = 30;
+	l[0] += 10;
+	assert_eq!(vec![11, 2, 30, 4], l.to_vec());
+}
 
-    for i in 1..100000 {
-        let mut h = Handle::new(vec![0u32;1000]);
-        insert_after(&mut s,  &mut h);
-        vec.push(h);
-    }
-    
-    std::io::stdin().read_line(line);
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "index out of bounds: the len is 3 but the index is 99")]
+fn index_out_of_range_test()
+{
+	let l = list![1, 2, 3];
+	let _ = l[99];
+}
 
-    while !vec.is_empty() {
-        vec.pop().unwrap().into_inner();
-    }
-    
-    std::io::stdin().read_line(line);
+#[cfg(test)]
+#[test]
+fn rposition_test()
+{
+	let l = list![1, 2, 3, 2, 1];
+	assert_eq!(Some(3), l.rposition(|v| *v == 2));
+	assert_eq!(Some(1), l.position(|v| *v == 2));
+	assert_eq!(Some(4), l.rposition(|v| *v == 1));
+	assert_eq!(None, l.rposition(|v| *v == 9));
+	let mut seen = Vec::new();
+	l.rposition(|v| { seen.push(*v); *v == 3 });
+	assert_eq!(vec![1, 2, 3], seen);
+	assert_eq!(None, List::<i32>::new().rposition(|_| true));
+}
+
+#[cfg(test)]
+#[test]
+fn cycle_iter_test()
+{
+	let l = list![1, 2, 3];
+	assert_eq!(vec![1, 2, 3, 1, 2, 3, 1], l.cycle_iter().take(7).cloned().collect::<Vec<_>>());
+	assert_eq!(vec![7], list![7].cycle_iter().take(1).cloned().collect::<Vec<_>>());
+	assert_eq!(vec![7, 7, 7], list![7].cycle_iter().take(3).cloned().collect::<Vec<_>>());
+
+	let empty: List<i32> = List::new();
+	let mut c = empty.cycle_iter();
+	assert_eq!(None, c.next());
+	assert_eq!(None, c.next());
+	assert_eq!(0, empty.cycle_iter().count());
+}
+
+#[cfg(test)]
+#[test]
+fn handle_index_test()
+{
+	let l = &mut List::new();
+	let h1 = l.push_tail(1);
+	let mut h2 = l.push_tail(2);
+	let h3 = l.push_tail(3);
+	assert_eq!((0, 1, 2), (h1.index(), h2.index(), h3.index()));
+
+	l.pop_head();
+	assert_eq!(0, h2.index());
+	assert_eq!(1, h3.index());
+
+	h2.move_to_tail();
+	assert_eq!((1, 0), (h2.index(), h3.index()));
+	l.push_head(0).detach();
+	assert_eq!(2, h2.index());
+	drop(h1);
+}
+
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "handle is not linked into a list")]
+fn handle_index_unlinked_test()
+{
+	let l = &mut List::new();
+	let h = l.push_tail(1);
+	l.clear();
+	h.index();
+}
+
+#[cfg(test)]
+#[test]
+fn swap_ends_test()
+{
+	let mut l = List::new();
+	let h1 = l.push_tail(1);
+	l.extend(2..4);
+	let h4 = l.push_tail(4);
+	l.swap_ends();
+	assert_eq!(vec![4, 2, 3, 1], l.to_vec());
+	assert_eq!(0, h4.index());
+	assert_eq!(3, h1.index());
+	assert!(l.validate());
+
+	assert_eq!(1, h1.unlink());
+	assert_eq!(4, h4.unlink());
+	l.swap_ends();
+	assert_eq!(vec![3, 2], l.to_vec());
+	l.pop_head();
+	l.swap_ends();
+	assert_eq!(vec![2], l.to_vec());
+	l.clear();
+	l.swap_ends();
+	assert!(l.is_empty() && l.validate());
+}
+
+#[cfg(test)]
+#[test]
+fn retain_order_test()
+{
+	let drops = Cell::new(0);
+	let l = &mut List::new();
+	let hs: Vec<_> = (1..6).map(|i| l.push_tail((i, DropCounter(&drops)))).collect();
+	let gone = [hs[1].as_ref() as *const _, hs[3].as_ref() as *const _];
+
+	let mut seen = Vec::new();
+	l.retain(|v| {
+		// every earlier rejection has already been dropped
+		seen.push((v.0, drops.get()));
+		!gone.iter().any(|g| core::ptr::eq(*g, v))
+	});
+	assert_eq!(vec![(1, 0), (2, 0), (3, 1), (4, 1), (5, 2)], seen);
+	assert_eq!(vec![1, 3, 5], l.iter().map(|v| v.0).collect::<Vec<_>>());
+	assert!(!hs[1].is_linked() && !hs[3].is_linked());
+	assert!(hs[0].is_linked() && hs[2].is_linked() && hs[4].is_linked());
+}
+
+#[cfg(test)]
+#[test]
+fn from_vec_test()
+{
+	let drops = Cell::new(0);
+	let v: Vec<_> = (0..4).map(|i| (i, DropCounter(&drops))).collect();
+	let l = List::from(v);
+	assert_eq!(4, l.len());
+	let v: Vec<_> = l.into();
+	assert_eq!(vec![0, 1, 2, 3], v.iter().map(|e| e.0).collect::<Vec<_>>());
+	assert_eq!(0, drops.get());
+	drop(v);
+	assert_eq!(4, drops.get());
+
+	let l: List<i32> = Vec::new().into();
+	assert!(l.is_empty());
+	assert!(Vec::from(l).is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn compact_test()
+{
+	let drops = Cell::new(0);
+	let mut l: List<_> = (0..6).map(|i| (i, DropCounter(&drops))).collect();
+	let h = l.push_tail((6, DropCounter(&drops)));
+	l.extend(Some((7, DropCounter(&drops))));
+	l.retain(|v| v.0 % 3 != 1);
+	l.reverse();
+	let before: Vec<*const _> = l.iter().map(|v| v as *const _).collect();
+	let held = h.as_ref() as *const _;
+
+	l.compact();
+	assert_eq!(vec![6, 5, 3, 2, 0], l.iter().map(|v| v.0).collect::<Vec<_>>());
+	assert_eq!(5, l.len());
+	assert_eq!(0, l.capacity() - l.len());
+	assert!(l.validate());
+	assert_eq!(3, drops.get());
+	// four arena slots, the handle's box and the sentinel
+	assert_eq!(6 * mem::size_of::<Link<(i32, DropCounter)>>(), l.heap_bytes());
+
+	// the list's own elements moved, the handle's didn't
+	let after: Vec<*const _> = l.iter().map(|v| v as *const _).collect();
+	assert_eq!(held, after[0]);
+	assert!(before[1..].iter().zip(&after[1..]).all(|(b, a)| b != a));
+	assert_eq!(6, h.unlink().0);
+	assert_eq!(4, l.len());
+
+	l.clear();
+	l.compact();
+	assert!(l.is_empty());
+	assert_eq!(8, drops.get());
+}
+
+#[cfg(test)]
+#[test]
+fn compact_arena_test()
+{
+	let drops = Cell::new(0);
+	let mut l: List<_> = (0..10).map(|i| (i, DropCounter(&drops))).collect();
+	l.retain(|v| v.0 % 2 == 0);
+	l.rotate_left(2);
+	l.compact();
+	assert_eq!(vec![4, 6, 8, 0, 2], l.iter().map(|v| v.0).collect::<Vec<_>>());
+
+	// one allocation, walked front to back
+	let at: Vec<_> = l.iter().map(|v| v as *const _ as usize).collect();
+	let link = mem::size_of::<Link<(i32, DropCounter)>>();
+	assert!(at.windows(2).all(|w| w[1] == w[0] + link));
+
+	// arena links come out like any other, they just aren't pooled
+	l.reserve(1);
+	assert_eq!(4, l.pop_head().unwrap().0);
+	assert_eq!(5, l.capacity());
+	// the popped arena slot still counts, next to the pooled link and the sentinel
+	assert_eq!(7 * link, l.heap_bytes());
+	l.push_head((3, DropCounter(&drops))).detach();
+	l.compact();
+	assert_eq!(vec![3, 6, 8, 0, 2], l.iter().map(|v| v.0).collect::<Vec<_>>());
+	assert!(l.validate());
+
+	// the links can outlive the list that compacted them
+	let mut rest = l.split_off(2);
+	let mut other = List::new();
+	other.append(&mut l);
+	drop(l);
+	rest.append(&mut other);
+	drop(other);
+	assert_eq!(vec![8, 0, 2, 3, 6], rest.iter().map(|v| v.0).collect::<Vec<_>>());
+	assert!(rest.validate());
+	let (odd, even): (List<_>, List<_>) = rest.partition(|v| v.0 % 2 == 1);
+	drop(odd);
+	assert_eq!(vec![8, 0, 2, 6], even.iter().map(|v| v.0).collect::<Vec<_>>());
+	drop(even);
+	assert_eq!(11, drops.get());
+}
+
+#[cfg(test)]
+#[test]
+fn head_handle_move_test()
+{
+	let mut l = list![1, 2];
+	let h3 = l.push_tail(3);
+	l.head_handle().unwrap().move_to_tail();
+	assert_eq!(vec![2, 3, 1], l.to_vec());
+	l.tail_handle().unwrap().move_to_head();
+	assert_eq!(vec![1, 2, 3], l.to_vec());
+
+	// already in place
+	l.head_handle().unwrap().move_to_head();
+	l.tail_handle().unwrap().move_to_tail();
+	assert_eq!(vec![1, 2, 3], l.to_vec());
+	assert!(l.validate());
+	assert_eq!(2, h3.index());
+
+	let mut single = list![1];
+	single.head_handle().unwrap().move_to_tail();
+	assert_eq!(vec![1], single.to_vec());
+	assert!(List::<i32>::new().head_handle().is_none());
+}
+
+#[cfg(test)]
+#[test]
+fn stride_iter_test()
+{
+	let l: List<i32> = (0..5).collect();
+	let i = l.stride_iter(2);
+	assert_eq!(3, i.len());
+	assert_eq!(vec![&0, &2, &4], i.collect::<Vec<_>>());
+	assert_eq!(vec![&0, &3], l.stride_iter(3).collect::<Vec<_>>());
+	assert_eq!(5, l.stride_iter(1).count());
+	assert_eq!(vec![&0], l.stride_iter(9).collect::<Vec<_>>());
+
+	let mut i = l.stride_iter(2);
+	i.next();
+	assert_eq!(2, i.len());
+	assert_eq!(0, List::<i32>::new().stride_iter(2).count());
+}
+
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "step must be non-zero")]
+fn stride_iter_zero_test()
+{
+	let l = list![1];
+	l.stride_iter(0);
+}
+
+#[cfg(test)]
+#[test]
+fn replace_at_test()
+{
+	let mut l = list![1];
+	let h2 = l.push_tail(2);
+	l.extend(Some(3));
+	assert_eq!(Some(2), l.replace_at(1, 9));
+	assert_eq!(vec![1, 9, 3], l.to_vec());
+	assert_eq!(None, l.replace_at(3, 4));
+	assert_eq!(3, l.len());
+	assert_eq!(9, h2.unlink());
+	assert_eq!(None, List::new().replace_at(0, 1));
+}
+
+#[cfg(test)]
+#[test]
+fn head_handle_drop_handle_test()
+{
+	let l = &mut List::new();
+	let p = l.push_tail(String::from("x"));
+	let r = l.head_handle().unwrap();
+	drop(p);
+	assert_eq!("x", r.unlink());
+	assert!(l.is_empty());
+
+	// the list took the element over
+	let p = l.push_tail(String::from("y"));
+	let r = l.tail_handle().unwrap();
+	drop(p);
+	drop(r);
+	assert_eq!(vec!["y"], l.iter().collect::<Vec<_>>());
+	l.clear();
+
+	// unlinked through the handle, the view frees the link
+	let p = l.push_tail(String::from("z"));
+	let r = l.head_handle().unwrap();
+	assert_eq!("z", p.unlink());
+	drop(r);
+	assert!(l.is_empty());
+
+	// a view that is done no longer keeps the handle from freeing its link
+	let p = l.push_tail(String::from("w"));
+	assert_eq!("w", l.head_handle().unwrap().get());
+	drop(p);
+	assert!(l.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "the element was unlinked through its handle")]
+fn head_handle_unlinked_by_handle_test()
+{
+	let l = &mut List::new();
+	let p = l.push_tail(String::from("x"));
+	let r = l.head_handle().unwrap();
+	p.unlink();
+	r.unlink();
 }